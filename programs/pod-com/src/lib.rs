@@ -9,11 +9,36 @@ use light_compressed_token::program::LightCompressedToken;
 use light_system_program::program::LightSystemProgram;
 use light_hasher::{DataHasher, Hasher, Poseidon};
 
+// SPL token support for channels denominated in a project token / stablecoin
+// rather than native lamports
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
 // Secure memory handling for cryptographic operations
 use memsec::{memzero, memeq};
 
 declare_id!("HEpGLgYsE1kP8aoYKyLFc3JVVrofS7T4zEA6fWBJsZps");
 
+/// Canonical, fixed Light Protocol program/PDA addresses this program trusts
+/// for ZK-compression CPIs. Every bare `AccountInfo` compression account is
+/// pinned to one of these via `#[account(address = ...)]`, the same defense
+/// `Program<'info, T>` already gives `light_system_program` and
+/// `compressed_token_program` automatically.
+mod light_protocol_ids {
+    use anchor_lang::prelude::*;
+
+    pub const NOOP_PROGRAM_ID: Pubkey =
+        anchor_lang::solana_program::pubkey!("noopb9bkMVfRPU8AQkHtKwMYZiFUjNRtMmV");
+    pub const ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey =
+        anchor_lang::solana_program::pubkey!("compr6CUsB5m2jS4Y3831ztGSTnDpnKJTKS95d64XVq");
+    // Single global authority PDA the account compression program signs CPIs
+    // with back into itself, and this program's own registration PDA with
+    // that program - both fixed once the deployment is registered.
+    pub const ACCOUNT_COMPRESSION_AUTHORITY_ID: Pubkey =
+        anchor_lang::solana_program::pubkey!("4qhLYcqyfrRUb4VPVLv8Ljtg1XF47VExcY9S48Ug78tg");
+    pub const REGISTERED_PROGRAM_ID: Pubkey =
+        anchor_lang::solana_program::pubkey!("DN3jNzugqv4WYZuaPyDEi2xf85U9F1uHM9Sc1K97Zzgs");
+}
+
 // =============================================================================
 // SECURE MEMORY UTILITIES
 // =============================================================================
@@ -120,11 +145,258 @@ const MAX_PARTICIPANTS_PER_CHANNEL: u32 = 1000; // Maximum participants in a cha
 const MAX_MESSAGE_CONTENT_LENGTH: usize = 1000; // Maximum message content length
 const RATE_LIMIT_MESSAGES_PER_MINUTE: u16 = 60; // Rate limit for messages
 const MIN_REPUTATION_FOR_CHANNELS: u64 = 50; // Minimum reputation to create channels
+const MAX_ENDPOINT_ADDRESS_LENGTH: usize = 128; // Maximum length of an announced endpoint address
+const MAX_ANNOUNCEMENT_ENDPOINTS: usize = 4; // One slot per known EndpointType
+const MAX_ERROR_MESSAGE_LENGTH: usize = 200; // Maximum length of a sanitized inter-agent error message
+const MAX_HTLC_AMOUNT: u64 = 10_000_000_000; // Max 10 SOL per HTLC, mirrors deposit_escrow's cap
+const CHANNEL_DISPUTE_WINDOW_SECONDS: i64 = 7 * 24 * 60 * 60; // 7 days
+
+// =============================================================================
+// TLV EXTENSION STREAM (Lightning-style forward-compatible records)
+// =============================================================================
+//
+// Accounts that want a forward-compatible upgrade path carry an `extension`
+// byte stream: an ordered sequence of (type, length, value) records appended
+// after their known fields. Types are serialized in strictly increasing order
+// with no duplicates. An unknown *even* type is a hard decode failure (the
+// producer is telling us something we must understand); an unknown *odd*
+// type is skipped and preserved verbatim so round-tripping through a client
+// that doesn't recognize it is lossless.
+
+/// Maximum serialized size of a TLV extension stream we allocate account
+/// space for.
+const MAX_TLV_EXTENSION_BYTES: usize = 256;
+
+/// A single decoded TLV record. `value` is the raw payload bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TlvRecord {
+    pub record_type: u64,
+    pub value: Vec<u8>,
+}
+
+/// An ordered stream of TLV records, decoded from (or to be encoded into) an
+/// account's `extension` byte stream.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TlvStream {
+    pub records: Vec<TlvRecord>,
+}
+
+/// Write a BigSize-style variable length integer (same encoding Lightning
+/// uses for TLV type/length fields): values below 0xfd are a single byte,
+/// larger values are prefixed with 0xfd/0xfe/0xff followed by a big-endian
+/// 2/4/8 byte integer.
+fn write_varint(out: &mut Vec<u8>, value: u64) {
+    if value < 0xfd {
+        out.push(value as u8);
+    } else if value <= 0xffff {
+        out.push(0xfd);
+        out.extend_from_slice(&(value as u16).to_be_bytes());
+    } else if value <= 0xffff_ffff {
+        out.push(0xfe);
+        out.extend_from_slice(&(value as u32).to_be_bytes());
+    } else {
+        out.push(0xff);
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+/// Read a BigSize-style variable length integer, returning the value and the
+/// number of bytes consumed.
+fn read_varint(data: &[u8]) -> Result<(u64, usize)> {
+    match data.first() {
+        None => Err(PodComError::InvalidTlvStream.into()),
+        Some(0xfd) => {
+            let bytes = data.get(1..3).ok_or(PodComError::InvalidTlvStream)?;
+            Ok((u16::from_be_bytes(bytes.try_into().unwrap()) as u64, 3))
+        }
+        Some(0xfe) => {
+            let bytes = data.get(1..5).ok_or(PodComError::InvalidTlvStream)?;
+            Ok((u32::from_be_bytes(bytes.try_into().unwrap()) as u64, 5))
+        }
+        Some(0xff) => {
+            let bytes = data.get(1..9).ok_or(PodComError::InvalidTlvStream)?;
+            Ok((u64::from_be_bytes(bytes.try_into().unwrap()), 9))
+        }
+        Some(&b) => Ok((b as u64, 1)),
+    }
+}
+
+impl TlvStream {
+    /// Encode the stream to its canonical byte representation: records in
+    /// strictly increasing `type` order, each as `varint(type) || varint(len) || value`.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for record in &self.records {
+            write_varint(&mut out, record.record_type);
+            write_varint(&mut out, record.value.len() as u64);
+            out.extend_from_slice(&record.value);
+        }
+        out
+    }
+
+    /// Decode a TLV byte stream, enforcing strictly increasing, non-duplicate
+    /// types and rejecting unknown mandatory (even) types. Unknown optional
+    /// (odd) types are kept in the returned stream so callers can re-encode
+    /// them unchanged.
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let mut records = Vec::new();
+        let mut offset = 0usize;
+        let mut last_type: Option<u64> = None;
+
+        while offset < data.len() {
+            let (record_type, type_len) = read_varint(&data[offset..])?;
+            offset += type_len;
+
+            if let Some(last) = last_type {
+                if record_type <= last {
+                    return Err(PodComError::InvalidTlvStream.into());
+                }
+            }
+            last_type = Some(record_type);
+
+            let (value_len, len_len) = read_varint(&data[offset..])?;
+            offset += len_len;
+
+            let value_len = value_len as usize;
+            let value = data
+                .get(offset..offset + value_len)
+                .ok_or(PodComError::InvalidTlvStream)?
+                .to_vec();
+            offset += value_len;
+
+            // Unknown even types are fatal; unknown odd types are tolerated
+            // and preserved as-is. Recognized types are validated by callers
+            // that interpret specific `record_type` values.
+            if record_type % 2 == 0 && !KNOWN_TLV_TYPES.contains(&record_type) {
+                return Err(PodComError::UnknownRequiredTlvType.into());
+            }
+
+            records.push(TlvRecord { record_type, value });
+        }
+
+        Ok(Self { records })
+    }
+}
+
+/// Central table of even (mandatory) TLV types recognized by this program
+/// version. Odd types never need to appear here since unknown odd types are
+/// always tolerated.
+const KNOWN_TLV_TYPES: &[u64] = &[];
+
+// =============================================================================
+// FEATURE NEGOTIATION (Lightning-style feature vectors)
+// =============================================================================
+//
+// `AgentAccount.capabilities` is a bitfield of feature *pairs*: feature N is
+// represented by bits 2N (mandatory) and 2N+1 (optional). A peer that sets the
+// mandatory bit is declaring "I require this feature from whoever I talk to";
+// setting only the optional bit means "I support this but don't require it".
+//
+// Only bit-pairs listed in `KNOWN_FEATURE_BITS` are understood by this version
+// of the program. Any *mandatory* bit outside that table is fatal for the
+// parties negotiating, exactly like an unknown required TLV/feature in BOLT.
+
+/// Feature: recipient supports `broadcast_message_compressed` (ZK compression).
+const FEATURE_COMPRESSED_MESSAGES: u32 = 0;
+/// Feature: recipient requires payloads to be pre-encrypted off-chain.
+const FEATURE_ENCRYPTED_PAYLOAD: u32 = 1;
+/// Feature: recipient understands the TLV extension stream on accounts.
+const FEATURE_TLV_EXTENSIONS: u32 = 2;
+
+/// Central table of feature numbers recognized by this program version.
+/// A mandatory bit (2N) for any feature N not in this table is always fatal.
+const KNOWN_FEATURE_BITS: &[u32] = &[
+    FEATURE_COMPRESSED_MESSAGES,
+    FEATURE_ENCRYPTED_PAYLOAD,
+    FEATURE_TLV_EXTENSIONS,
+];
+
+/// Mandatory (even) bit position for feature `n`.
+const fn mandatory_bit(n: u32) -> u64 {
+    1u64 << (2 * n)
+}
+
+/// Optional (odd) bit position for feature `n`.
+const fn optional_bit(n: u32) -> u64 {
+    1u64 << (2 * n + 1)
+}
+
+/// Returns true if `bits` has either the mandatory or optional bit set for feature `n`.
+fn feature_understood(bits: u64, n: u32) -> bool {
+    bits & (mandatory_bit(n) | optional_bit(n)) != 0
+}
+
+/// Verify that every mandatory feature bit set in `features` is one this program
+/// recognizes. Unknown optional (odd) bits are always tolerated.
+fn check_known_mandatory_bits(features: u64) -> Result<()> {
+    let mut recognized_mask: u64 = 0;
+    for &n in KNOWN_FEATURE_BITS {
+        recognized_mask |= mandatory_bit(n) | optional_bit(n);
+    }
+    // Any mandatory (even) bit set outside the recognized mask is fatal.
+    let unknown_mandatory_mask = !recognized_mask & 0x5555_5555_5555_5555;
+    if features & unknown_mandatory_mask != 0 {
+        return Err(PodComError::UnknownRequiredFeature.into());
+    }
+    Ok(())
+}
+
+/// Compute whether `sender` can talk to `recipient`: every mandatory feature
+/// bit `sender` has set must be understood by `recipient` (either its
+/// mandatory or optional bit for that same feature must be set).
+fn verify_feature_compatibility(sender: u64, recipient: u64) -> Result<()> {
+    check_known_mandatory_bits(sender)?;
+    check_known_mandatory_bits(recipient)?;
+
+    for &n in KNOWN_FEATURE_BITS {
+        if sender & mandatory_bit(n) != 0 && !feature_understood(recipient, n) {
+            return Err(PodComError::UnknownRequiredFeature.into());
+        }
+    }
+    Ok(())
+}
+
+/// Intersection of the feature sets two agents both understand: for each known
+/// feature, the negotiated bit is set (as mandatory if either side requires it,
+/// otherwise as optional) only if both sides understand that feature at all.
+pub fn negotiate_features(a: u64, b: u64) -> u64 {
+    let mut negotiated: u64 = 0;
+    for &n in KNOWN_FEATURE_BITS {
+        if feature_understood(a, n) && feature_understood(b, n) {
+            if a & mandatory_bit(n) != 0 || b & mandatory_bit(n) != 0 {
+                negotiated |= mandatory_bit(n);
+            } else {
+                negotiated |= optional_bit(n);
+            }
+        }
+    }
+    negotiated
+}
 
 // Account Space Constants with optimized struct packing (PERF-02)
 // All structs use #[repr(C)] for consistent memory layout and optimal performance
-const AGENT_ACCOUNT_SPACE: usize = 8 + 32 + 8 + 8 + 8 + (4 + MAX_METADATA_URI_LENGTH) + 1 + 7; // 276 bytes (optimized layout)
+const AGENT_ACCOUNT_SPACE: usize = 8
+    + 32
+    + 8
+    + 8
+    + 8
+    + (4 + MAX_METADATA_URI_LENGTH)
+    + (4 + MAX_TLV_EXTENSION_BYTES) // extension TLV stream
+    + 8  // last_seen
+    + 8  // heartbeats_sent
+    + 1
+    + 7; // 552 bytes (optimized layout)
 const MESSAGE_ACCOUNT_SPACE: usize = 8 + 32 + 32 + 32 + 8 + 8 + 1 + 1 + 1 + 5; // 128 bytes (optimized layout)
+const DECOMPRESSED_MESSAGE_SPACE: usize = 8 // discriminator
+    + 32 // channel
+    + 32 // sender
+    + 32 // content_hash
+    + (4 + 100) // ipfs_hash
+    + 1  // message_type
+    + 8  // created_at
+    + 9  // edited_at
+    + 33 // reply_to
+    + 1; // bump - 260 bytes
 const CHANNEL_ACCOUNT_SPACE: usize = 8
     + 32 // creator
     + 8  // fee_per_message
@@ -136,13 +408,65 @@ const CHANNEL_ACCOUNT_SPACE: usize = 8
     + (4 + MAX_CHANNEL_DESCRIPTION_LENGTH) // description
     + 1  // visibility
     + 1  // is_active
+    + 1  // is_closing
+    + 8  // dispute_window_ends_at
+    + 32 // settlement_root
+    + 8  // event_sequence
+    + 32 // compression_tree
+    + 32 // compression_queue
+    + 32 // compression_root
+    + 32 // fee_mint
+    + 8  // compression_capacity
+    + 8  // compression_leaf_count
     + 1  // bump
-    + 5; // _reserved - 333 bytes (optimized layout)
+    + 5; // _reserved - 531 bytes (optimized layout)
 const CHANNEL_PARTICIPANT_SPACE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 1 + 1 + 6; // 104 bytes (optimized layout)
 const CHANNEL_INVITATION_SPACE: usize = 8 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 1 + 1 + 1 + 5; // 168 bytes (optimized layout)
-const CHANNEL_MESSAGE_SPACE: usize =
-    8 + 32 + 32 + 33 + 8 + 9 + (4 + MAX_MESSAGE_CONTENT_LENGTH) + 1 + 1 + 6; // 1134 bytes (optimized layout)
+const CHANNEL_OFFER_SPACE: usize = 8 + 32 + 32 + 32 + 8 + 8 + 8 + 5 + 4 + 1 + 1 + 5; // 144 bytes (optimized layout)
+const CHANNEL_MESSAGE_SPACE: usize = 8
+    + 32
+    + 32
+    + 33
+    + 8
+    + 9
+    + (4 + MAX_MESSAGE_CONTENT_LENGTH)
+    + (4 + MAX_TLV_EXTENSION_BYTES) // extension TLV stream
+    + 1
+    + 1
+    + 6; // 1394 bytes (optimized layout)
 const ESCROW_ACCOUNT_SPACE: usize = 8 + 32 + 32 + 8 + 8 + 1 + 7; // 96 bytes (already optimal)
+// Message session account space: discriminator + two agent keys + two sets of
+// (next_local, next_remote, last_acked_hash) + bump
+const MESSAGE_SESSION_SPACE: usize = 8 + 32 + 32 + 2 * (8 + 8 + 32) + 1 + 7;
+// Error message account space: discriminator + sender + two optional targets + data + timestamp + nonce + bump
+const ERROR_MESSAGE_SPACE: usize = 8
+    + 32 // sender
+    + 33 // target_agent (Option<Pubkey>)
+    + 33 // target_channel (Option<Pubkey>)
+    + (4 + MAX_ERROR_MESSAGE_LENGTH) // data
+    + 8  // created_at
+    + 8  // nonce
+    + 1  // bump
+    + 7; // _reserved
+// Agent announcement account space: discriminator + agent + endpoints vec + signature + timestamp + nonce + bump
+const AGENT_ANNOUNCEMENT_SPACE: usize = 8
+    + 32 // agent
+    + (4 + MAX_ANNOUNCEMENT_ENDPOINTS * (1 + 4 + MAX_ENDPOINT_ADDRESS_LENGTH)) // endpoints
+    + 64 // signature
+    + 8  // announced_at
+    + 8  // nonce
+    + 1; // bump
+// HTLC account space: discriminator + payer + payee + hash_lock + timeout + amount + status + preimage + bump
+const HTLC_ACCOUNT_SPACE: usize = 8
+    + 32 // payer
+    + 32 // payee
+    + 32 // hash_lock
+    + 8  // timeout
+    + 8  // amount
+    + 1  // status
+    + 33 // preimage (Option<[u8; 32]>)
+    + 1  // bump
+    + 7; // _reserved
 
 // Error codes
 #[error_code]
@@ -179,6 +503,64 @@ pub enum PodComError {
     HashingFailed,
     #[msg("Secure memory allocation failed")]
     SecureMemoryAllocationFailed,
+    #[msg("Recipient does not understand a required (mandatory) feature bit")]
+    UnknownRequiredFeature,
+    #[msg("Malformed TLV extension stream")]
+    InvalidTlvStream,
+    #[msg("TLV extension stream contains an unrecognized mandatory type")]
+    UnknownRequiredTlvType,
+    #[msg("Announcement is stale: timestamp/nonce did not advance")]
+    StaleAnnouncement,
+    #[msg("Too many endpoints in agent announcement")]
+    TooManyEndpoints,
+    #[msg("Peer claims to have acknowledged a message number that was never sent")]
+    DataLossProtectViolation,
+    #[msg("HTLC preimage does not match the stored hash lock")]
+    HtlcPreimageMismatch,
+    #[msg("HTLC has already been claimed or refunded")]
+    HtlcNotLocked,
+    #[msg("HTLC timeout has already passed")]
+    HtlcExpired,
+    #[msg("HTLC timeout has not yet passed")]
+    HtlcNotExpired,
+    #[msg("Channel is closing or already closed")]
+    ChannelClosing,
+    #[msg("Channel is not in a closing state")]
+    ChannelNotClosing,
+    #[msg("Dispute window has not yet elapsed")]
+    DisputeWindowActive,
+    #[msg("Settlement amount exceeds the channel's recorded escrow balance")]
+    InvalidSettlement,
+    #[msg("Splice may specify at most one of splice_in_amount or splice_out_amount")]
+    InvalidSpliceAmount,
+    #[msg("Splice-out would drop escrow below outstanding per-message obligations")]
+    SpliceBelowObligations,
+    #[msg("Channel offer has expired")]
+    OfferExpired,
+    #[msg("Channel offer is no longer active")]
+    OfferInactive,
+    #[msg("Channel offer has reached its redemption cap")]
+    OfferRedemptionCapReached,
+    #[msg("Participant registry has no free slots")]
+    RegistryFull,
+    #[msg("Merkle tree / nullifier queue is not the pair registered for this channel")]
+    CompressionAccountMismatch,
+    #[msg("Channel has no compression tree/queue configured")]
+    CompressionNotConfigured,
+    #[msg("Merkle inclusion proof does not fold up to the channel's stored compression root")]
+    InvalidMerkleProof,
+    #[msg("Token mint does not match the channel's configured fee_mint")]
+    FeeMintMismatch,
+    #[msg("Channel is configured for SPL-token fees but no token accounts were provided")]
+    MissingTokenAccounts,
+    #[msg("Light Protocol compression account does not match the program's trusted address")]
+    InvalidCompressionAccount,
+    #[msg("Lamport transfer would overflow or underflow an account balance")]
+    LamportArithmeticOverflow,
+    #[msg("Compression capacity must be greater than zero")]
+    InvalidCompressionCapacity,
+    #[msg("Channel's compression queue has reached its configured capacity; rotate to a fresh tree via configure_channel_compression")]
+    CompressionQueueFull,
 }
 
 // Message types
@@ -207,6 +589,31 @@ pub enum ChannelVisibility {
     Private,
 }
 
+// Transport endpoint type tag for agent announcements, modeled on Lightning's
+// node_announcement address descriptors.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum EndpointType {
+    Ipv4,
+    Ipv6,
+    TorV3,
+    DnsHttps,
+}
+
+// A single reachable transport endpoint, tagged by type.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct AgentEndpoint {
+    pub endpoint_type: EndpointType,
+    pub address: String, // host:port, onion address, or relay URL depending on type
+}
+
+// Hash-time-locked escrow payment status
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum HtlcStatus {
+    Locked,
+    Claimed,
+    Refunded,
+}
+
 // Program Events for monitoring and indexing
 #[event]
 pub struct AgentRegistered {
@@ -230,6 +637,7 @@ pub struct ChannelCreated {
     pub creator: Pubkey,
     pub name: String,
     pub visibility: ChannelVisibility,
+    pub sequence: u64,
     pub timestamp: i64,
 }
 
@@ -237,6 +645,26 @@ pub struct ChannelCreated {
 pub struct ChannelJoined {
     pub channel: Pubkey,
     pub participant: Pubkey,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+// Mirrors `ChannelJoined` but for the uncompressed join path, where a
+// first-class `ChannelParticipant` PDA (rather than a compressed leaf) is
+// the thing an indexer would otherwise have to scan for.
+#[event]
+pub struct ParticipantJoined {
+    pub channel: Pubkey,
+    pub participant: Pubkey,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParticipantLeft {
+    pub channel: Pubkey,
+    pub participant: Pubkey,
+    pub sequence: u64,
     pub timestamp: i64,
 }
 
@@ -245,25 +673,184 @@ pub struct MessageBroadcast {
     pub channel: Pubkey,
     pub sender: Pubkey,
     pub message_type: MessageType,
+    pub content_hash: [u8; 32],
+    pub reply_to: Option<Pubkey>,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowDeposited {
+    pub channel: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+    pub sequence: u64,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct EscrowDeposit {
+pub struct EscrowWithdrawn {
     pub channel: Pubkey,
     pub depositor: Pubkey,
     pub amount: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InvitationSent {
+    pub channel: Pubkey,
+    pub inviter: Pubkey,
+    pub invitee: Pubkey,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InvitationAccepted {
+    pub channel: Pubkey,
+    pub invitee: Pubkey,
+    pub sequence: u64,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct EscrowWithdrawal {
+pub struct ChannelOfferCreated {
+    pub channel: Pubkey,
+    pub issuer: Pubkey,
+    pub offer: Pubkey,
+    pub max_redemptions: Option<u32>,
+    pub expires_at: i64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OfferRedeemed {
+    pub channel: Pubkey,
+    pub offer: Pubkey,
+    pub redeemer: Pubkey,
+    pub redemptions_used: u32,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeaturesNegotiated {
+    pub agent_a: Pubkey,
+    pub agent_b: Pubkey,
+    pub negotiated: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgentAnnounced {
+    pub agent: Pubkey,
+    pub endpoints: Vec<AgentEndpoint>,
+    pub signature: [u8; 64],
+    pub announced_at: i64,
+    pub nonce: u64,
+}
+
+#[event]
+pub struct ChannelClosed {
+    pub channel: Pubkey,
+    pub settlement_root: [u8; 32],
+    pub dispute_window_ends_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelFinalized {
+    pub channel: Pubkey,
+    pub swept_lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SpliceExecuted {
     pub channel: Pubkey,
     pub depositor: Pubkey,
+    pub max_participants_before: u32,
+    pub max_participants_after: u32,
+    pub escrow_balance_before: u64,
+    pub escrow_balance_after: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CompressedLeafAppended {
+    pub channel: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub leaf: [u8; 32],
+    pub leaf_index: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MessageDecompressed {
+    pub channel: Pubkey,
+    pub sender: Pubkey,
+    pub content_hash: [u8; 32],
+    pub leaf: [u8; 32],
+    pub leaf_index: u64,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct HtlcCreated {
+    pub htlc: Pubkey,
+    pub payer: Pubkey,
+    pub payee: Pubkey,
     pub amount: u64,
+    pub hash_lock: [u8; 32],
+    pub timeout: i64,
+}
+
+#[event]
+pub struct HtlcClaimed {
+    pub htlc: Pubkey,
+    pub payee: Pubkey,
+    pub preimage: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct HtlcRefunded {
+    pub htlc: Pubkey,
+    pub payer: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ErrorMessageSent {
+    pub sender: Pubkey,
+    pub target_agent: Option<Pubkey>,
+    pub target_channel: Option<Pubkey>,
+    pub data: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Heartbeat {
+    pub agent: Pubkey,
+    pub nonce: u64,
+    pub pong_length: Option<u16>,
     pub timestamp: i64,
 }
 
+#[event]
+pub struct SessionReestablished {
+    pub agent: Pubkey,
+    pub peer: Pubkey,
+    /// Inclusive-exclusive range `[behind_from, behind_to)` of message numbers
+    /// the caller is missing from `peer` and should re-fetch from IPFS.
+    pub behind_from: u64,
+    pub behind_to: u64,
+}
+
 // Channel account structure with optimized memory layout (PERF-02)
 #[account]
 #[repr(C)]
@@ -278,6 +865,16 @@ pub struct ChannelAccount {
     pub description: String,           // 4 + 200 bytes (max 200 chars)
     pub visibility: ChannelVisibility, // 1 byte
     pub is_active: bool,               // 1 byte
+    pub is_closing: bool,              // 1 byte - cooperative close in progress, dispute window open
+    pub dispute_window_ends_at: i64,   // 8 bytes - closing becomes finalizable after this timestamp
+    pub settlement_root: [u8; 32],     // 32 bytes - keccak over (depositor, amount) pairs at close time
+    pub event_sequence: u64,           // 8 bytes - monotonic counter stamped on every emitted channel event
+    pub compression_tree: Pubkey,      // 32 bytes - registered Light Protocol Merkle tree for this channel, Pubkey::default() if unconfigured
+    pub compression_queue: Pubkey,     // 32 bytes - registered Light Protocol nullifier queue paired with compression_tree
+    pub compression_root: [u8; 32],    // 32 bytes - Poseidon Merkle root light clients can prove message/participant history against
+    pub fee_mint: Pubkey,              // 32 bytes - SPL mint escrow/fees are denominated in, Pubkey::default() for native lamports
+    pub compression_capacity: u64,     // 8 bytes - max leaves this program will append before requiring a tree rotation
+    pub compression_leaf_count: u64,   // 8 bytes - leaves appended to the current compression_tree so far
     pub bump: u8,                      // 1 byte
     _reserved: [u8; 5],                // 5 bytes (padding for alignment)
 }
@@ -296,6 +893,104 @@ pub struct ChannelParticipant {
     _reserved: [u8; 6],       // 6 bytes (padding for alignment)
 }
 
+// Fixed capacity of the optional per-channel `ParticipantRegistry`. Picked to
+// keep the account comfortably small (a few KB) while covering the common
+// case; channels that outgrow it fall back to scanning `ChannelParticipant`
+// PDAs off-chain as before.
+const PARTICIPANT_REGISTRY_CAPACITY: usize = 128;
+const PARTICIPANT_REGISTRY_BITMAP_BYTES: usize = PARTICIPANT_REGISTRY_CAPACITY / 8;
+
+// One packed registry entry: agent pubkey, join time, and a flags byte
+// (bit 0 = live/occupied). Laid out so a membership check is a single bitmap
+// read plus, on a hit, one contiguous slot read.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct ParticipantSlot {
+    pub agent: Pubkey, // 32 bytes
+    pub joined_at: i64, // 8 bytes
+    pub flags: u8,      // 1 byte - bit 0: occupied
+}
+
+impl Default for ParticipantSlot {
+    fn default() -> Self {
+        Self {
+            agent: Pubkey::default(),
+            joined_at: 0,
+            flags: 0,
+        }
+    }
+}
+
+// Optional, opt-in per-channel membership index. `join_channel` /
+// `leave_channel` keep this in sync with the per-participant PDAs
+// (reusing freed slots via the occupied bitmap) when a caller supplies it,
+// and `broadcast_message` can check membership against it in O(1) instead
+// of trusting a passed-in `ChannelParticipant` account.
+#[account]
+pub struct ParticipantRegistry {
+    pub channel: Pubkey,
+    pub occupied_bitmap: [u8; PARTICIPANT_REGISTRY_BITMAP_BYTES],
+    pub slots: [ParticipantSlot; PARTICIPANT_REGISTRY_CAPACITY],
+    pub bump: u8,
+}
+
+const PARTICIPANT_REGISTRY_SPACE: usize = 8
+    + 32 // channel
+    + PARTICIPANT_REGISTRY_BITMAP_BYTES
+    + PARTICIPANT_REGISTRY_CAPACITY * (32 + 8 + 1) // slots
+    + 1; // bump
+
+fn registry_bit_get(bitmap: &[u8; PARTICIPANT_REGISTRY_BITMAP_BYTES], index: usize) -> bool {
+    bitmap[index / 8] & (1 << (index % 8)) != 0
+}
+
+fn registry_bit_set(bitmap: &mut [u8; PARTICIPANT_REGISTRY_BITMAP_BYTES], index: usize, value: bool) {
+    if value {
+        bitmap[index / 8] |= 1 << (index % 8);
+    } else {
+        bitmap[index / 8] &= !(1 << (index % 8));
+    }
+}
+
+// Deterministic home slot for an agent: open addressing with linear probing,
+// so lookup/insert are O(1) in the common case (single bitmap + slot read)
+// instead of a scan over every slot. `occupied_bitmap` marks a slot as
+// "non-empty" - it stays set across a `leave_channel` (the slot becomes a
+// tombstone) so later probes for a *different* agent that collided on the
+// same home slot don't stop early; `ParticipantSlot::flags` bit 0 is the
+// actual live/occupied marker checked by callers.
+fn registry_home_slot(agent: &Pubkey) -> usize {
+    let digest = anchor_lang::solana_program::keccak::hash(agent.as_ref()).to_bytes();
+    let mut home_bytes = [0u8; 8];
+    home_bytes.copy_from_slice(&digest[..8]);
+    (u64::from_le_bytes(home_bytes) % PARTICIPANT_REGISTRY_CAPACITY as u64) as usize
+}
+
+fn registry_find(registry: &ParticipantRegistry, agent: &Pubkey) -> Option<usize> {
+    let home = registry_home_slot(agent);
+    for probe in 0..PARTICIPANT_REGISTRY_CAPACITY {
+        let i = (home + probe) % PARTICIPANT_REGISTRY_CAPACITY;
+        if !registry_bit_get(&registry.occupied_bitmap, i) {
+            // A genuinely empty slot on the probe chain means this agent was
+            // never inserted - no tombstone would leave a gap like this.
+            return None;
+        }
+        if registry.slots[i].flags & 1 != 0 && registry.slots[i].agent == *agent {
+            return Some(i);
+        }
+    }
+    None
+}
+
+// First slot this agent's probe chain can claim: either a never-used slot,
+// or a tombstone left behind by a departed agent that collided on the same
+// home slot.
+fn registry_insert_slot(registry: &ParticipantRegistry, agent: &Pubkey) -> Option<usize> {
+    let home = registry_home_slot(agent);
+    (0..PARTICIPANT_REGISTRY_CAPACITY)
+        .map(|probe| (home + probe) % PARTICIPANT_REGISTRY_CAPACITY)
+        .find(|&i| !registry_bit_get(&registry.occupied_bitmap, i) || registry.slots[i].flags & 1 == 0)
+}
+
 // Channel invitation account structure (for private channels)
 // SECURITY ENHANCEMENT (MED-01): Cryptographically secure invitation system
 // PERFORMANCE OPTIMIZATION (PERF-02): Optimized memory layout
@@ -315,6 +1010,26 @@ pub struct ChannelInvitation {
     _reserved: [u8; 5],             // 5 bytes (padding for alignment)
 }
 
+// Reusable, signed channel offer (BOLT12-style "offer"), the broadcast-invite
+// complement to the single-use `ChannelInvitation` above: many agents can
+// redeem the same offer instead of the issuer minting one invitation per
+// invitee.
+#[account]
+#[repr(C)]
+pub struct ChannelOffer {
+    pub channel: Pubkey,           // 32 bytes
+    pub issuer: Pubkey,            // 32 bytes
+    pub offer_hash: [u8; 32],      // 32 bytes - keccak(channel, issuer, nonce)
+    pub nonce: u64,                // 8 bytes
+    pub created_at: i64,           // 8 bytes
+    pub expires_at: i64,           // 8 bytes
+    pub max_redemptions: Option<u32>, // 5 bytes - None = unlimited
+    pub redemptions_used: u32,     // 4 bytes
+    pub is_active: bool,           // 1 byte
+    pub bump: u8,                  // 1 byte
+    _reserved: [u8; 5],            // 5 bytes (padding for alignment)
+}
+
 // Channel message account structure (for broadcast messages)
 // PERFORMANCE OPTIMIZATION (PERF-02): Optimized memory layout
 #[account]
@@ -326,11 +1041,32 @@ pub struct ChannelMessage {
     pub created_at: i64,           // 8 bytes
     pub edited_at: Option<i64>,    // 9 bytes (1 for Option + 8 for i64)
     pub content: String,           // 4 + 1000 bytes (max content)
+    pub extension: Vec<u8>,        // 4 + MAX_TLV_EXTENSION_BYTES bytes - TLV extension stream
     pub message_type: MessageType, // 1 byte
     pub bump: u8,                  // 1 byte
     _reserved: [u8; 6],            // 6 bytes (padding for alignment)
 }
 
+// Materialized form of a compressed channel message, recovered on-chain by
+// decompress_channel_message once its Merkle inclusion proof has been
+// verified. Mirrors CompressedChannelMessage's fields (content lives off
+// chain via ipfs_hash, only its hash is proven/stored) rather than
+// ChannelMessage's, since the full content text was never on-chain to begin
+// with.
+#[account]
+#[repr(C)]
+pub struct DecompressedChannelMessage {
+    pub channel: Pubkey,              // 32 bytes
+    pub sender: Pubkey,                // 32 bytes
+    pub content_hash: [u8; 32],       // 32 bytes
+    pub ipfs_hash: String,             // 4 + 100 bytes
+    pub message_type: MessageType,     // 1 byte
+    pub created_at: i64,               // 8 bytes
+    pub edited_at: Option<i64>,        // 9 bytes
+    pub reply_to: Option<Pubkey>,      // 33 bytes
+    pub bump: u8,                      // 1 byte
+}
+
 // Escrow account structure with optimized memory layout (PERF-02)
 #[account]
 #[repr(C)]
@@ -352,10 +1088,246 @@ pub struct AgentAccount {
     pub reputation: u64,      // 8 bytes
     pub last_updated: i64,    // 8 bytes
     pub metadata_uri: String, // 4 + MAX_METADATA_URI_LENGTH bytes
+    pub extension: Vec<u8>,   // 4 + MAX_TLV_EXTENSION_BYTES bytes - TLV extension stream
+    pub last_seen: i64,       // 8 bytes - last heartbeat timestamp, for liveness/presence
+    pub heartbeats_sent: u64, // 8 bytes - heartbeats in the current sliding-window (rate limiting)
     pub bump: u8,             // 1 byte
     _reserved: [u8; 7],       // 7 bytes (padding for alignment)
 }
 
+// Signed multi-endpoint agent announcement, modeled on Lightning's
+// node_announcement, so off-chain indexers can build a peer directory.
+#[account]
+pub struct AgentAnnouncement {
+    pub agent: Pubkey,                 // 32 bytes - agent PDA this announcement is for
+    pub endpoints: Vec<AgentEndpoint>, // at most one entry per EndpointType
+    pub signature: [u8; 64],           // ed25519 signature over the announcement by the agent's wallet
+    pub announced_at: i64,             // monotonically increasing timestamp
+    pub nonce: u64,                    // monotonically increasing nonce, tie-breaks same-second announcements
+    pub bump: u8,
+}
+
+// Per-pair session state for resumable, gap-detecting message delivery,
+// modeled on Lightning's channel_reestablish / data-loss-protect. Keyed on
+// the canonically-ordered pair of agent PDAs so either side can derive the
+// same account regardless of who initiates.
+#[account]
+#[repr(C)]
+pub struct MessageSession {
+    pub agent_lo: Pubkey, // lexicographically smaller agent PDA
+    pub agent_hi: Pubkey, // lexicographically larger agent PDA
+    pub lo_next_local: u64,       // next message number agent_lo will send
+    pub lo_next_remote: u64,      // next message number agent_lo expects from agent_hi
+    pub lo_last_acked_hash: [u8; 32], // commitment to the last message agent_lo acknowledged
+    pub hi_next_local: u64,       // next message number agent_hi will send
+    pub hi_next_remote: u64,      // next message number agent_hi expects from agent_lo
+    pub hi_last_acked_hash: [u8; 32], // commitment to the last message agent_hi acknowledged
+    pub bump: u8,
+    _reserved: [u8; 7],
+}
+
+// Sanitized inter-agent error/abort notification, so one agent can tell
+// another on-chain why an interaction failed.
+#[account]
+#[repr(C)]
+pub struct ErrorMessage {
+    pub sender: Pubkey,
+    pub target_agent: Option<Pubkey>,
+    pub target_channel: Option<Pubkey>,
+    pub data: String, // sanitized, bounded human-readable failure reason
+    pub created_at: i64,
+    pub nonce: u64,
+    pub bump: u8,
+    _reserved: [u8; 7],
+}
+
+// Hash-time-locked conditional escrow payment, modeled on Lightning HTLCs:
+// funds are locked against a hash lock and released either to the payee (on
+// presentation of the matching preimage before `timeout`) or back to the
+// payer (after `timeout` if never claimed).
+#[account]
+#[repr(C)]
+pub struct HtlcAccount {
+    pub payer: Pubkey,
+    pub payee: Pubkey,
+    pub hash_lock: [u8; 32], // keccak256(preimage)
+    pub timeout: i64,        // absolute unix timestamp after which only refund is possible
+    pub amount: u64,         // lamports locked
+    pub status: HtlcStatus,
+    pub preimage: Option<[u8; 32]>, // revealed preimage, stored on claim for off-chain verification
+    pub bump: u8,
+    _reserved: [u8; 7],
+}
+
+/// Canonical seed for this program's Light Protocol CPI authority PDA. It
+/// holds no data; it exists only so the account_compression program has a
+/// signer it can attribute to this program, derived the same way on every
+/// call so a caller cannot substitute an authority they control instead.
+const CPI_AUTHORITY_SEED: &[u8] = b"cpi_authority";
+
+/// Append a Poseidon leaf to a Light Protocol Merkle tree and its paired
+/// nullifier queue via CPI, signed by this program's canonical CPI
+/// authority PDA. Returns the leaf index assigned by the account
+/// compression program, read back from CPI return data, or 0 when that
+/// program sets no (or too short) return data.
+///
+/// This program doesn't have the real account-compression IDL to build
+/// and verify the CPI call against in this environment, so it cannot rely
+/// on the on-chain queue's own "full" rejection behaving as expected here.
+/// Callers must not treat a successful `invoke_signed` alone as proof the
+/// queue had room; instead they check `compression_leaf_count` against the
+/// channel's program-controlled `compression_capacity` before calling this,
+/// and increment it after this returns `Ok`.
+#[allow(clippy::too_many_arguments)]
+fn cpi_append_compressed_leaf<'info>(
+    account_compression_program: &AccountInfo<'info>,
+    registered_program_id: &AccountInfo<'info>,
+    noop_program: &AccountInfo<'info>,
+    account_compression_authority: &AccountInfo<'info>,
+    merkle_tree: &AccountInfo<'info>,
+    nullifier_queue: &AccountInfo<'info>,
+    cpi_authority_pda: &AccountInfo<'info>,
+    cpi_authority_bump: u8,
+    leaf: [u8; 32],
+) -> Result<u64> {
+    use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+    use anchor_lang::solana_program::program::{get_return_data, invoke_signed};
+
+    // Anchor-style instruction discriminator: first 8 bytes of
+    // sha256("global:<instruction_name>"), computed the same way a client
+    // without this program's IDL would build the CPI by hand.
+    let mut data = anchor_lang::solana_program::hash::hash(b"global:append_leaf")
+        .to_bytes()[..8]
+        .to_vec();
+    data.extend_from_slice(&leaf);
+
+    let instruction = Instruction {
+        program_id: account_compression_program.key(),
+        accounts: vec![
+            AccountMeta::new_readonly(registered_program_id.key(), false),
+            AccountMeta::new_readonly(noop_program.key(), false),
+            AccountMeta::new_readonly(account_compression_authority.key(), false),
+            AccountMeta::new(merkle_tree.key(), false),
+            AccountMeta::new(nullifier_queue.key(), false),
+            AccountMeta::new_readonly(cpi_authority_pda.key(), true),
+        ],
+        data,
+    };
+
+    invoke_signed(
+        &instruction,
+        &[
+            registered_program_id.clone(),
+            noop_program.clone(),
+            account_compression_authority.clone(),
+            merkle_tree.clone(),
+            nullifier_queue.clone(),
+            cpi_authority_pda.clone(),
+            account_compression_program.clone(),
+        ],
+        &[&[CPI_AUTHORITY_SEED, &[cpi_authority_bump]]],
+    )?;
+
+    let leaf_index = get_return_data()
+        .filter(|(program_id, _)| *program_id == account_compression_program.key())
+        .map(|(_, data)| data)
+        .filter(|data| data.len() >= 8)
+        .map(|data| u64::from_le_bytes(data[..8].try_into().unwrap()))
+        .unwrap_or(0);
+
+    Ok(leaf_index)
+}
+
+/// Mark a leaf as spent in a Light Protocol nullifier queue via CPI, signed
+/// by this program's canonical CPI authority PDA, so it cannot be
+/// decompressed more than once.
+fn cpi_nullify_compressed_leaf<'info>(
+    account_compression_program: &AccountInfo<'info>,
+    registered_program_id: &AccountInfo<'info>,
+    account_compression_authority: &AccountInfo<'info>,
+    merkle_tree: &AccountInfo<'info>,
+    nullifier_queue: &AccountInfo<'info>,
+    cpi_authority_pda: &AccountInfo<'info>,
+    cpi_authority_bump: u8,
+    leaf: [u8; 32],
+    leaf_index: u64,
+) -> Result<()> {
+    use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+    use anchor_lang::solana_program::program::invoke_signed;
+
+    let mut data = anchor_lang::solana_program::hash::hash(b"global:nullify_leaf")
+        .to_bytes()[..8]
+        .to_vec();
+    data.extend_from_slice(&leaf);
+    data.extend_from_slice(&leaf_index.to_le_bytes());
+
+    let instruction = Instruction {
+        program_id: account_compression_program.key(),
+        accounts: vec![
+            AccountMeta::new_readonly(registered_program_id.key(), false),
+            AccountMeta::new_readonly(account_compression_authority.key(), false),
+            AccountMeta::new(merkle_tree.key(), false),
+            AccountMeta::new(nullifier_queue.key(), false),
+            AccountMeta::new_readonly(cpi_authority_pda.key(), true),
+        ],
+        data,
+    };
+
+    invoke_signed(
+        &instruction,
+        &[
+            registered_program_id.clone(),
+            account_compression_authority.clone(),
+            merkle_tree.clone(),
+            nullifier_queue.clone(),
+            cpi_authority_pda.clone(),
+            account_compression_program.clone(),
+        ],
+        &[&[CPI_AUTHORITY_SEED, &[cpi_authority_bump]]],
+    )
+}
+
+/// Lexicographically smaller of two pubkeys, used to derive a canonical,
+/// order-independent session PDA for a pair of agents.
+fn min_pubkey(a: &Pubkey, b: &Pubkey) -> Pubkey {
+    if a.to_bytes() <= b.to_bytes() { *a } else { *b }
+}
+
+/// Lexicographically larger of two pubkeys (see `min_pubkey`).
+fn max_pubkey(a: &Pubkey, b: &Pubkey) -> Pubkey {
+    if a.to_bytes() > b.to_bytes() { *a } else { *b }
+}
+
+/// Advance a channel's per-channel event sequence counter and return the
+/// value to stamp on the event about to be emitted, so off-chain consumers
+/// can detect gaps in the log and resync from `getProgramAccounts` instead
+/// of trusting a potentially incomplete stream.
+fn next_sequence(channel: &mut ChannelAccount) -> u64 {
+    channel.event_sequence = channel.event_sequence.saturating_add(1);
+    channel.event_sequence
+}
+
+/// Strip control characters and terminal escape sequences from an
+/// inter-agent error message before it is stored on-chain, so a malicious
+/// sender cannot inject payloads that attack a recipient's logs or terminal
+/// when the message is later displayed off-chain. A CSI sequence
+/// (`ESC [` ... final byte `@`..=`~`) is removed whole; any other `ESC` is
+/// removed together with the character that follows it.
+fn sanitize_error_data(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            if chars.next() == Some('[') {
+                let _ = chars.by_ref().find(|n| ('@'..='~').contains(n));
+            }
+        } else if !c.is_control() {
+            out.push(c);
+        }
+    }
+    out
+}
+
 // Message account structure with optimized memory layout (PERF-02)
 #[account]
 #[repr(C)]
@@ -386,6 +1358,7 @@ pub struct CompressedChannelMessage {
     pub created_at: i64,           // 8 bytes
     pub edited_at: Option<i64>,    // 9 bytes
     pub reply_to: Option<Pubkey>,  // 33 bytes
+    pub extension: Vec<u8>,        // variable - canonical TLV extension stream
 }
 
 // Implement DataHasher for Light Protocol v0.6.0 compatibility with secure memory
@@ -395,6 +1368,7 @@ impl DataHasher for CompressedChannelMessage {
         let mut size = 32 + 32 + 32 + self.ipfs_hash.len() + 1 + 8; // base fields
         if self.edited_at.is_some() { size += 8; }
         if self.reply_to.is_some() { size += 32; }
+        size += self.extension.len(); // TLV extension stream, hashed after base fields
         
         // Use secure memory for sensitive hash computation
         let mut secure_buf = SecureBuffer::new(size).unwrap();
@@ -434,8 +1408,17 @@ impl DataHasher for CompressedChannelMessage {
         }
         if let Some(reply_to) = self.reply_to {
             data[offset..offset+32].copy_from_slice(&reply_to.to_bytes());
+            offset += 32;
         }
-        
+
+        // Fold the canonical TLV extension bytes into the hash deterministically,
+        // after all base fields, so adding new TLV records doesn't shift
+        // existing offsets and break previously computed hashes.
+        if !self.extension.is_empty() {
+            data[offset..offset + self.extension.len()].copy_from_slice(&self.extension);
+            offset += self.extension.len();
+        }
+
         // Perform hash computation on secure data
         H::hash(&data[..offset])
     }
@@ -482,6 +1465,35 @@ impl DataHasher for CompressedChannelParticipant {
     }
 }
 
+/// A Merkle inclusion proof for one leaf of a channel's compression tree:
+/// the leaf itself, its index (which fixes left/right ordering at every
+/// level), and the sibling hash at each level from the bottom up.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MerkleProofEntry {
+    pub leaf: [u8; 32],
+    pub leaf_index: u64,
+    pub siblings: Vec<[u8; 32]>,
+}
+
+/// Fold a leaf up to a Merkle root using its inclusion proof. At level `k`,
+/// bit `k` of the leaf index selects which side the leaf (or its running
+/// parent hash) sits on relative to `siblings[k]`.
+fn fold_merkle_proof(proof: &MerkleProofEntry) -> Result<[u8; 32]> {
+    let mut current = proof.leaf;
+    for (level, sibling) in proof.siblings.iter().enumerate() {
+        let mut buf = [0u8; 64];
+        if (proof.leaf_index >> level) & 1 == 0 {
+            buf[..32].copy_from_slice(&current);
+            buf[32..].copy_from_slice(sibling);
+        } else {
+            buf[..32].copy_from_slice(sibling);
+            buf[32..].copy_from_slice(&current);
+        }
+        current = Poseidon::hash(&buf).map_err(|_| PodComError::InvalidMerkleProof)?;
+    }
+    Ok(current)
+}
+
 // IPFS Content structures for off-chain storage
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct ChannelMessageContent {
@@ -515,10 +1527,10 @@ pub mod pod_com {
         if metadata_uri.len() > MAX_METADATA_URI_LENGTH {
             return Err(PodComError::InvalidMetadataUriLength.into());
         }
-        if capabilities > u64::MAX / 2 {
-            // Reasonable upper bound
-            return Err(PodComError::Unauthorized.into()); // Reusing error for invalid capabilities
-        }
+        // Feature-bit validation: any mandatory (even) bit outside the recognized
+        // feature table is rejected up front, the same way an unknown required
+        // feature would be rejected during negotiation with a peer.
+        check_known_mandatory_bits(capabilities)?;
 
         let agent = &mut ctx.accounts.agent_account;
         let clock = Clock::get()?;
@@ -526,8 +1538,11 @@ pub mod pod_com {
         agent.pubkey = ctx.accounts.signer.key();
         agent.capabilities = capabilities;
         agent.metadata_uri = metadata_uri.clone();
+        agent.extension = Vec::new();
         agent.reputation = 100; // Initial reputation
         agent.last_updated = clock.unix_timestamp;
+        agent.last_seen = clock.unix_timestamp;
+        agent.heartbeats_sent = 0;
         agent.bump = ctx.bumps.agent_account;
 
         // Emit event for monitoring
@@ -556,6 +1571,23 @@ pub mod pod_com {
         let message = &mut ctx.accounts.message_account;
         let clock = Clock::get()?;
 
+        // Feature negotiation: reject if the recipient doesn't understand a
+        // feature the sender has marked mandatory.
+        verify_feature_compatibility(
+            ctx.accounts.sender_agent.capabilities,
+            ctx.accounts.recipient_agent.capabilities,
+        )?;
+        let negotiated = negotiate_features(
+            ctx.accounts.sender_agent.capabilities,
+            ctx.accounts.recipient_agent.capabilities,
+        );
+        emit!(FeaturesNegotiated {
+            agent_a: ctx.accounts.sender_agent.key(),
+            agent_b: ctx.accounts.recipient_agent.key(),
+            negotiated,
+            timestamp: clock.unix_timestamp,
+        });
+
         // IMPORTANT: Use agent PDA as sender for consistency across all message types
         // This ensures all messages are associated with registered agents, not raw wallets
         message.sender = ctx.accounts.sender_agent.key();
@@ -610,6 +1642,7 @@ pub mod pod_com {
         let agent = &mut ctx.accounts.agent_account;
 
         if let Some(caps) = capabilities {
+            check_known_mandatory_bits(caps)?;
             agent.capabilities = caps;
         }
 
@@ -626,7 +1659,205 @@ pub mod pod_com {
         Ok(())
     }
 
-    // Update message status (e.g., mark as delivered or read)
+    // Publish (or refresh) a signed multi-endpoint announcement for off-chain discovery
+    pub fn announce_agent(
+        ctx: Context<AnnounceAgent>,
+        mut endpoints: Vec<AgentEndpoint>,
+        announced_at: i64,
+        nonce: u64,
+        signature: [u8; 64],
+    ) -> Result<()> {
+        if endpoints.len() > MAX_ANNOUNCEMENT_ENDPOINTS {
+            return Err(PodComError::TooManyEndpoints.into());
+        }
+
+        // "At most one address per type, silently drop extras": keep the
+        // first occurrence of each endpoint type in the submitted order.
+        let mut seen_types = [false; MAX_ANNOUNCEMENT_ENDPOINTS];
+        endpoints.retain(|endpoint| {
+            let idx = endpoint.endpoint_type as usize;
+            if seen_types[idx] {
+                false
+            } else {
+                seen_types[idx] = true;
+                true
+            }
+        });
+
+        for endpoint in &endpoints {
+            if endpoint.address.len() > MAX_ENDPOINT_ADDRESS_LENGTH {
+                return Err(PodComError::InvalidMetadataUriLength.into()); // Reusing error for oversized address
+            }
+        }
+
+        let announcement = &mut ctx.accounts.agent_announcement;
+
+        // Reject stale announcements: the (timestamp, nonce) pair must advance
+        // relative to whatever was previously stored - a later timestamp, or
+        // the same timestamp with a larger nonce.
+        if announcement.agent != Pubkey::default()
+            && (announced_at, nonce) <= (announcement.announced_at, announcement.nonce)
+        {
+            return Err(PodComError::StaleAnnouncement.into());
+        }
+
+        announcement.agent = ctx.accounts.agent_account.key();
+        announcement.endpoints = endpoints.clone();
+        announcement.signature = signature;
+        announcement.announced_at = announced_at;
+        announcement.nonce = nonce;
+        announcement.bump = ctx.bumps.agent_announcement;
+
+        emit!(AgentAnnounced {
+            agent: announcement.agent,
+            endpoints,
+            signature,
+            announced_at,
+            nonce,
+        });
+
+        msg!("Agent announcement published for {:?}", announcement.agent);
+        Ok(())
+    }
+
+    // Reestablish a messaging session with a peer agent after a reconnect,
+    // reporting exactly which message numbers the caller is missing.
+    pub fn reestablish_session(
+        ctx: Context<ReestablishSession>,
+        next_local: u64,
+        next_remote: u64,
+        last_acked_hash: [u8; 32],
+    ) -> Result<()> {
+        let caller = ctx.accounts.agent_account.key();
+        let peer = ctx.accounts.peer_agent_account.key();
+        let session = &mut ctx.accounts.session;
+
+        // Initialize a freshly-created session PDA.
+        if session.agent_lo == Pubkey::default() && session.agent_hi == Pubkey::default() {
+            session.agent_lo = min_pubkey(&caller, &peer);
+            session.agent_hi = max_pubkey(&caller, &peer);
+            session.bump = ctx.bumps.session;
+        }
+
+        let caller_is_lo = caller == session.agent_lo;
+
+        let peer_next_local = if caller_is_lo { session.hi_next_local } else { session.lo_next_local };
+
+        // DATA-LOSS-PROTECT: the caller cannot have validly acknowledged a
+        // message number the peer never sent.
+        if next_remote > peer_next_local {
+            return Err(PodComError::DataLossProtectViolation.into());
+        }
+
+        if caller_is_lo {
+            session.lo_next_local = next_local;
+            session.lo_next_remote = next_remote;
+            session.lo_last_acked_hash = last_acked_hash;
+        } else {
+            session.hi_next_local = next_local;
+            session.hi_next_remote = next_remote;
+            session.hi_last_acked_hash = last_acked_hash;
+        }
+
+        emit!(SessionReestablished {
+            agent: caller,
+            peer,
+            behind_from: next_remote,
+            behind_to: peer_next_local,
+        });
+
+        msg!(
+            "Session reestablished between {:?} and {:?}, behind range [{}, {})",
+            caller,
+            peer,
+            next_remote,
+            peer_next_local
+        );
+        Ok(())
+    }
+
+    // Lightweight liveness/presence heartbeat, spam-resistant via the same
+    // sliding-window rate limiting used for channel messages.
+    pub fn heartbeat(
+        ctx: Context<SendHeartbeat>,
+        nonce: u64,
+        pong_length: Option<u16>,
+    ) -> Result<()> {
+        let agent = &mut ctx.accounts.agent_account;
+        let clock = Clock::get()?;
+        let current_time = clock.unix_timestamp;
+
+        if agent.last_seen > 0 {
+            let time_since_last = current_time - agent.last_seen;
+
+            if time_since_last < 1 {
+                return Err(PodComError::RateLimitExceeded.into());
+            }
+
+            if time_since_last < 60 {
+                if agent.heartbeats_sent >= RATE_LIMIT_MESSAGES_PER_MINUTE as u64 {
+                    return Err(PodComError::RateLimitExceeded.into());
+                }
+                agent.heartbeats_sent = agent.heartbeats_sent.checked_add(1)
+                    .ok_or(PodComError::RateLimitExceeded)?;
+            } else {
+                agent.heartbeats_sent = 1;
+            }
+        } else {
+            agent.heartbeats_sent = 1;
+        }
+
+        agent.last_seen = current_time;
+        agent.last_updated = current_time;
+
+        emit!(Heartbeat {
+            agent: agent.key(),
+            nonce,
+            pong_length,
+            timestamp: current_time,
+        });
+
+        msg!("Heartbeat from {:?}", agent.key());
+        Ok(())
+    }
+
+    // Send a sanitized, bounded error/abort notification to another agent or channel
+    pub fn send_error_message(
+        ctx: Context<SendErrorMessage>,
+        target_agent: Option<Pubkey>,
+        target_channel: Option<Pubkey>,
+        data: String,
+        nonce: u64,
+    ) -> Result<()> {
+        let sanitized = sanitize_error_data(&data);
+        if sanitized.len() > MAX_ERROR_MESSAGE_LENGTH {
+            return Err(PodComError::MessageContentTooLong.into());
+        }
+
+        let clock = Clock::get()?;
+        let error_message = &mut ctx.accounts.error_message;
+
+        error_message.sender = ctx.accounts.sender_agent.key();
+        error_message.target_agent = target_agent;
+        error_message.target_channel = target_channel;
+        error_message.data = sanitized.clone();
+        error_message.created_at = clock.unix_timestamp;
+        error_message.nonce = nonce;
+        error_message.bump = ctx.bumps.error_message;
+
+        emit!(ErrorMessageSent {
+            sender: error_message.sender,
+            target_agent,
+            target_channel,
+            data: sanitized,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Error message sent from {:?}", error_message.sender);
+        Ok(())
+    }
+
+    // Update message status (e.g., mark as delivered or read)
     pub fn update_message_status(
         ctx: Context<UpdateMessageStatus>,
         new_status: MessageStatus,
@@ -672,6 +1903,7 @@ pub mod pod_com {
         visibility: ChannelVisibility,
         max_participants: u32,
         fee_per_message: u64,
+        fee_mint: Option<Pubkey>,
     ) -> Result<()> {
         // Comprehensive input validation
         if name.trim().is_empty() {
@@ -703,8 +1935,28 @@ pub mod pod_com {
         channel.fee_per_message = fee_per_message;
         channel.escrow_balance = 0;
         channel.created_at = clock.unix_timestamp;
+        channel.is_closing = false;
+        channel.dispute_window_ends_at = 0;
+        channel.settlement_root = [0u8; 32];
+        channel.event_sequence = 0;
+        channel.compression_tree = Pubkey::default();
+        channel.compression_queue = Pubkey::default();
+        channel.compression_root = [0u8; 32];
+        channel.compression_capacity = 0;
+        channel.compression_leaf_count = 0;
+        channel.fee_mint = fee_mint.unwrap_or_default();
         channel.bump = ctx.bumps.channel_account;
 
+        let sequence = next_sequence(channel);
+        emit!(ChannelCreated {
+            channel: channel.key(),
+            creator: channel.creator,
+            name: channel.name.clone(),
+            visibility: channel.visibility,
+            sequence,
+            timestamp: channel.created_at,
+        });
+
         msg!("Channel created: {:?}", channel.creator);
         Ok(())
     }
@@ -721,21 +1973,51 @@ pub mod pod_com {
         }
 
         let clock = Clock::get()?;
+        let channel_mint = ctx.accounts.channel_account.fee_mint;
+
+        if channel_mint != Pubkey::default() {
+            // SPL-token channel: move tokens from the depositor into the
+            // PDA-owned vault instead of moving lamports.
+            let mint = ctx.accounts.token_mint.as_ref().ok_or(PodComError::MissingTokenAccounts)?;
+            let vault = ctx.accounts.escrow_token_vault.as_ref().ok_or(PodComError::MissingTokenAccounts)?;
+            let depositor_token_account = ctx
+                .accounts
+                .depositor_token_account
+                .as_ref()
+                .ok_or(PodComError::MissingTokenAccounts)?;
+            let token_program = ctx.accounts.token_program.as_ref().ok_or(PodComError::MissingTokenAccounts)?;
 
-        // Transfer SOL from depositor to escrow PDA
-        let transfer_instruction = anchor_lang::solana_program::system_instruction::transfer(
-            &ctx.accounts.depositor.key(),
-            &ctx.accounts.escrow_account.key(),
-            amount,
-        );
+            if mint.key() != channel_mint {
+                return Err(PodComError::FeeMintMismatch.into());
+            }
 
-        anchor_lang::solana_program::program::invoke(
-            &transfer_instruction,
-            &[
-                ctx.accounts.depositor.to_account_info(),
-                ctx.accounts.escrow_account.to_account_info(),
-            ],
-        )?;
+            token::transfer(
+                CpiContext::new(
+                    token_program.to_account_info(),
+                    Transfer {
+                        from: depositor_token_account.to_account_info(),
+                        to: vault.to_account_info(),
+                        authority: ctx.accounts.depositor.to_account_info(),
+                    },
+                ),
+                amount,
+            )?;
+        } else {
+            // Transfer SOL from depositor to escrow PDA
+            let transfer_instruction = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.depositor.key(),
+                &ctx.accounts.escrow_account.key(),
+                amount,
+            );
+
+            anchor_lang::solana_program::program::invoke(
+                &transfer_instruction,
+                &[
+                    ctx.accounts.depositor.to_account_info(),
+                    ctx.accounts.escrow_account.to_account_info(),
+                ],
+            )?;
+        }
 
         // Initialize escrow account data
         let escrow = &mut ctx.accounts.escrow_account;
@@ -750,7 +2032,20 @@ pub mod pod_com {
         // Update channel escrow balance
         channel.escrow_balance += amount;
 
-        msg!("Deposited {} lamports to escrow", amount);
+        let sequence = next_sequence(channel);
+        emit!(EscrowDeposited {
+            channel: channel.key(),
+            depositor: escrow.depositor,
+            amount,
+            sequence,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "Deposited {} {} to escrow",
+            amount,
+            if channel_mint == Pubkey::default() { "lamports" } else { "tokens" }
+        );
         Ok(())
     }
 
@@ -766,17 +2061,54 @@ pub mod pod_com {
             return Err(PodComError::InsufficientFunds.into());
         }
 
-        // Transfer SOL from escrow PDA back to depositor
-        **ctx
-            .accounts
-            .escrow_account
-            .to_account_info()
-            .try_borrow_mut_lamports()? -= amount;
-        **ctx
-            .accounts
-            .depositor
-            .to_account_info()
-            .try_borrow_mut_lamports()? += amount;
+        let channel_mint = ctx.accounts.channel_account.fee_mint;
+
+        if channel_mint != Pubkey::default() {
+            // SPL-token channel: move tokens from the PDA-owned vault back to
+            // the depositor, signed by the escrow PDA's own seeds.
+            let vault = ctx.accounts.escrow_token_vault.as_ref().ok_or(PodComError::MissingTokenAccounts)?;
+            let depositor_token_account = ctx
+                .accounts
+                .depositor_token_account
+                .as_ref()
+                .ok_or(PodComError::MissingTokenAccounts)?;
+            let token_program = ctx.accounts.token_program.as_ref().ok_or(PodComError::MissingTokenAccounts)?;
+
+            let channel_key = ctx.accounts.channel_account.key();
+            let depositor_key = ctx.accounts.depositor.key();
+            let escrow_bump = ctx.accounts.escrow_account.bump;
+            let escrow_seeds: &[&[u8]] = &[
+                b"escrow",
+                channel_key.as_ref(),
+                depositor_key.as_ref(),
+                &[escrow_bump],
+            ];
+
+            token::transfer(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    Transfer {
+                        from: vault.to_account_info(),
+                        to: depositor_token_account.to_account_info(),
+                        authority: ctx.accounts.escrow_account.to_account_info(),
+                    },
+                    &[escrow_seeds],
+                ),
+                amount,
+            )?;
+        } else {
+            // Transfer SOL from escrow PDA back to depositor
+            **ctx
+                .accounts
+                .escrow_account
+                .to_account_info()
+                .try_borrow_mut_lamports()? -= amount;
+            **ctx
+                .accounts
+                .depositor
+                .to_account_info()
+                .try_borrow_mut_lamports()? += amount;
+        }
 
         // Update account data
         let escrow = &mut ctx.accounts.escrow_account;
@@ -785,7 +2117,334 @@ pub mod pod_com {
         escrow.amount -= amount;
         channel.escrow_balance -= amount;
 
-        msg!("Withdrew {} lamports from escrow", amount);
+        let clock = Clock::get()?;
+        let sequence = next_sequence(channel);
+        emit!(EscrowWithdrawn {
+            channel: channel.key(),
+            depositor: escrow.depositor,
+            amount,
+            sequence,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "Withdrew {} {} from escrow",
+            amount,
+            if channel_mint == Pubkey::default() { "lamports" } else { "tokens" }
+        );
+        Ok(())
+    }
+
+    // Lock a conditional, hash-time-locked payment (HTLC)
+    pub fn create_htlc(
+        ctx: Context<CreateHtlc>,
+        payee: Pubkey,
+        amount: u64,
+        hash_lock: [u8; 32],
+        timeout: i64,
+    ) -> Result<()> {
+        if amount == 0 || amount > MAX_HTLC_AMOUNT {
+            return Err(PodComError::InsufficientFunds.into());
+        }
+        let clock = Clock::get()?;
+        if timeout <= clock.unix_timestamp {
+            return Err(PodComError::HtlcExpired.into());
+        }
+
+        let transfer_instruction = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.payer.key(),
+            &ctx.accounts.htlc_account.key(),
+            amount,
+        );
+        anchor_lang::solana_program::program::invoke(
+            &transfer_instruction,
+            &[
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.htlc_account.to_account_info(),
+            ],
+        )?;
+
+        let htlc = &mut ctx.accounts.htlc_account;
+        htlc.payer = ctx.accounts.payer.key();
+        htlc.payee = payee;
+        htlc.hash_lock = hash_lock;
+        htlc.timeout = timeout;
+        htlc.amount = amount;
+        htlc.status = HtlcStatus::Locked;
+        htlc.preimage = None;
+        htlc.bump = ctx.bumps.htlc_account;
+
+        emit!(HtlcCreated {
+            htlc: htlc.key(),
+            payer: htlc.payer,
+            payee: htlc.payee,
+            amount,
+            hash_lock,
+            timeout,
+        });
+
+        msg!("HTLC created for {} lamports, payee {:?}", amount, payee);
+        Ok(())
+    }
+
+    // Claim an HTLC by presenting the preimage of its hash lock
+    pub fn claim_htlc(ctx: Context<ClaimHtlc>, preimage: [u8; 32]) -> Result<()> {
+        let clock = Clock::get()?;
+        let htlc = &mut ctx.accounts.htlc_account;
+
+        if htlc.status != HtlcStatus::Locked {
+            return Err(PodComError::HtlcNotLocked.into());
+        }
+        if clock.unix_timestamp >= htlc.timeout {
+            return Err(PodComError::HtlcExpired.into());
+        }
+
+        let computed_hash = anchor_lang::solana_program::keccak::hash(&preimage).to_bytes();
+        // SECURITY: constant-time comparison against the stored hash lock to
+        // avoid leaking timing information about a near-correct preimage.
+        let matches = unsafe { memeq(computed_hash.as_ptr(), htlc.hash_lock.as_ptr(), 32) };
+        if !matches {
+            return Err(PodComError::HtlcPreimageMismatch.into());
+        }
+
+        let amount = htlc.amount;
+        let htlc_info = ctx.accounts.htlc_account.to_account_info();
+        let payee_info = ctx.accounts.payee.to_account_info();
+        let new_htlc_lamports = htlc_info
+            .lamports()
+            .checked_sub(amount)
+            .ok_or(PodComError::LamportArithmeticOverflow)?;
+        let new_payee_lamports = payee_info
+            .lamports()
+            .checked_add(amount)
+            .ok_or(PodComError::LamportArithmeticOverflow)?;
+        **htlc_info.try_borrow_mut_lamports()? = new_htlc_lamports;
+        **payee_info.try_borrow_mut_lamports()? = new_payee_lamports;
+
+        // Status flag set atomically with the payout so claim and refund are
+        // mutually exclusive.
+        htlc.status = HtlcStatus::Claimed;
+        htlc.preimage = Some(preimage);
+
+        emit!(HtlcClaimed {
+            htlc: htlc.key(),
+            payee: htlc.payee,
+            preimage,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("HTLC claimed by {:?}", htlc.payee);
+        Ok(())
+    }
+
+    // Refund an expired, unclaimed HTLC back to the payer
+    pub fn refund_htlc(ctx: Context<RefundHtlc>) -> Result<()> {
+        let clock = Clock::get()?;
+        let htlc = &mut ctx.accounts.htlc_account;
+
+        if htlc.status != HtlcStatus::Locked {
+            return Err(PodComError::HtlcNotLocked.into());
+        }
+        if clock.unix_timestamp < htlc.timeout {
+            return Err(PodComError::HtlcNotExpired.into());
+        }
+
+        let amount = htlc.amount;
+        let htlc_info = ctx.accounts.htlc_account.to_account_info();
+        let payer_info = ctx.accounts.payer.to_account_info();
+        let new_htlc_lamports = htlc_info
+            .lamports()
+            .checked_sub(amount)
+            .ok_or(PodComError::LamportArithmeticOverflow)?;
+        let new_payer_lamports = payer_info
+            .lamports()
+            .checked_add(amount)
+            .ok_or(PodComError::LamportArithmeticOverflow)?;
+        **htlc_info.try_borrow_mut_lamports()? = new_htlc_lamports;
+        **payer_info.try_borrow_mut_lamports()? = new_payer_lamports;
+
+        htlc.status = HtlcStatus::Refunded;
+
+        emit!(HtlcRefunded {
+            htlc: htlc.key(),
+            payer: htlc.payer,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("HTLC refunded to {:?}", htlc.payer);
+        Ok(())
+    }
+
+    // Begin a cooperative close of a channel. Only the creator can initiate,
+    // and must supply the final settlement (who gets how much of the escrow
+    // pool). The channel stops accepting new joins/broadcasts immediately,
+    // but depositors can still reclaim their own escrow balance via
+    // `withdraw_escrow` during the dispute window.
+    pub fn close_channel(
+        ctx: Context<CloseChannel>,
+        settlements: Vec<(Pubkey, u64)>,
+    ) -> Result<()> {
+        let channel = &mut ctx.accounts.channel_account;
+        let clock = Clock::get()?;
+
+        if channel.is_closing {
+            return Err(PodComError::ChannelClosing.into());
+        }
+
+        let total: u64 = settlements
+            .iter()
+            .try_fold(0u64, |acc, (_, amount)| acc.checked_add(*amount))
+            .ok_or(PodComError::InvalidSettlement)?;
+        if total != channel.escrow_balance {
+            return Err(PodComError::InvalidSettlement.into());
+        }
+
+        let settlement_root = {
+            let data = settlements.try_to_vec()?;
+            anchor_lang::solana_program::keccak::hash(&data).0
+        };
+
+        channel.is_closing = true;
+        channel.dispute_window_ends_at = clock
+            .unix_timestamp
+            .checked_add(CHANNEL_DISPUTE_WINDOW_SECONDS)
+            .ok_or(PodComError::InvalidSettlement)?;
+        channel.settlement_root = settlement_root;
+
+        emit!(ChannelClosed {
+            channel: channel.key(),
+            settlement_root,
+            dispute_window_ends_at: channel.dispute_window_ends_at,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Channel {} entering cooperative close", channel.key());
+        Ok(())
+    }
+
+    // Finalize a channel close once the dispute window has elapsed. Sweeps
+    // any escrow accounts passed in `remaining_accounts` (as
+    // `[escrow_account, depositor]` pairs) that depositors never reclaimed,
+    // then deactivates the channel and returns its rent to the creator.
+    pub fn finalize_close(ctx: Context<FinalizeClose>) -> Result<()> {
+        let clock = Clock::get()?;
+        let channel = &mut ctx.accounts.channel_account;
+
+        if !channel.is_closing {
+            return Err(PodComError::ChannelNotClosing.into());
+        }
+        if clock.unix_timestamp < channel.dispute_window_ends_at {
+            return Err(PodComError::DisputeWindowActive.into());
+        }
+
+        if ctx.remaining_accounts.len() % 2 != 0 {
+            return Err(PodComError::InvalidSettlement.into());
+        }
+
+        let mut swept_lamports: u64 = 0;
+        for pair in ctx.remaining_accounts.chunks(2) {
+            let escrow_info = &pair[0];
+            let depositor_info = &pair[1];
+
+            let escrow: Account<EscrowAccount> = Account::try_from(escrow_info)?;
+            if escrow.channel != channel.key() {
+                return Err(PodComError::Unauthorized.into());
+            }
+            if escrow.depositor != *depositor_info.key {
+                return Err(PodComError::Unauthorized.into());
+            }
+
+            let dust = escrow_info.lamports();
+            swept_lamports = swept_lamports
+                .checked_add(dust)
+                .ok_or(PodComError::InvalidSettlement)?;
+
+            **escrow_info.try_borrow_mut_lamports()? = 0;
+            **depositor_info.try_borrow_mut_lamports()? += dust;
+        }
+
+        channel.is_active = false;
+
+        emit!(ChannelFinalized {
+            channel: channel.key(),
+            swept_lamports,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Channel {} finalized", channel.key());
+        Ok(())
+    }
+
+    // Create the optional O(1) membership index for a channel. Creator-only;
+    // existing participants are not backfilled and must rejoin to appear.
+    pub fn create_participant_registry(ctx: Context<CreateParticipantRegistry>) -> Result<()> {
+        let registry = &mut ctx.accounts.participant_registry;
+        registry.channel = ctx.accounts.channel_account.key();
+        registry.occupied_bitmap = [0u8; PARTICIPANT_REGISTRY_BITMAP_BYTES];
+        registry.slots = [ParticipantSlot::default(); PARTICIPANT_REGISTRY_CAPACITY];
+        registry.bump = ctx.bumps.participant_registry;
+
+        msg!("Participant registry created for {:?}", registry.channel);
+        Ok(())
+    }
+
+    /// Register the Light Protocol Merkle tree / nullifier queue pair that
+    /// `broadcast_message_compressed` and `join_channel_compressed` are
+    /// allowed to append to for this channel. Creator-only, and callable
+    /// again to rotate to a fresh tree once the old one fills up.
+    pub fn configure_channel_compression(
+        ctx: Context<ConfigureChannelCompression>,
+        merkle_tree: Pubkey,
+        nullifier_queue: Pubkey,
+        capacity: u64,
+    ) -> Result<()> {
+        let channel = &mut ctx.accounts.channel_account;
+
+        if channel.creator != ctx.accounts.creator.key() {
+            return Err(PodComError::Unauthorized.into());
+        }
+        if capacity == 0 {
+            return Err(PodComError::InvalidCompressionCapacity.into());
+        }
+
+        channel.compression_tree = merkle_tree;
+        channel.compression_queue = nullifier_queue;
+        // This program does not have the real account-compression IDL to
+        // validate against in this environment, so it cannot confirm the
+        // on-chain queue's own remaining capacity. Track and enforce a
+        // program-controlled ceiling here instead, resetting the count on
+        // every (re)configure so rotating to a fresh tree/queue pair also
+        // resets the budget.
+        channel.compression_capacity = capacity;
+        channel.compression_leaf_count = 0;
+
+        msg!(
+            "Compression configured for channel {:?}: tree {:?}, queue {:?}, capacity {}",
+            channel.name,
+            merkle_tree,
+            nullifier_queue,
+            capacity
+        );
+        Ok(())
+    }
+
+    /// Cache the current Poseidon root of the channel's compression tree
+    /// on-chain so `batch_sync_compressed_messages` can verify client-supplied
+    /// inclusion proofs against it. Creator-only; called whenever the
+    /// off-chain indexer observes the tree has advanced.
+    pub fn update_compression_root(
+        ctx: Context<UpdateCompressionRoot>,
+        new_root: [u8; 32],
+    ) -> Result<()> {
+        let channel = &mut ctx.accounts.channel_account;
+
+        if channel.creator != ctx.accounts.creator.key() {
+            return Err(PodComError::Unauthorized.into());
+        }
+
+        channel.compression_root = new_root;
+
+        msg!("Compression root updated for channel {:?}", channel.name);
         Ok(())
     }
 
@@ -889,9 +2548,36 @@ pub mod pod_com {
             if let Some(invitation) = &mut ctx.accounts.invitation_account {
                 invitation.is_accepted = true;
                 invitation.is_used = true; // Prevent reuse of the same invitation
+
+                let sequence = next_sequence(channel);
+                emit!(InvitationAccepted {
+                    channel: channel.key(),
+                    invitee: invitation.invitee,
+                    sequence,
+                    timestamp: clock.unix_timestamp,
+                });
             }
         }
 
+        if let Some(registry) = &mut ctx.accounts.participant_registry {
+            let free_slot =
+                registry_insert_slot(registry, &participant.participant).ok_or(PodComError::RegistryFull)?;
+            registry.slots[free_slot] = ParticipantSlot {
+                agent: participant.participant,
+                joined_at: clock.unix_timestamp,
+                flags: 1,
+            };
+            registry_bit_set(&mut registry.occupied_bitmap, free_slot, true);
+        }
+
+        let sequence = next_sequence(channel);
+        emit!(ParticipantJoined {
+            channel: channel.key(),
+            participant: participant.participant,
+            sequence,
+            timestamp: clock.unix_timestamp,
+        });
+
         msg!(
             "User {:?} joined channel {:?}",
             participant.participant,
@@ -916,6 +2602,24 @@ pub mod pod_com {
         // Update channel participant count
         channel.current_participants -= 1;
 
+        if let Some(registry) = &mut ctx.accounts.participant_registry {
+            if let Some(slot) = registry_find(registry, &participant.participant) {
+                // Tombstone, don't clear `occupied_bitmap`: a later agent
+                // whose home slot collided with this one still needs its
+                // probe chain to continue past this index.
+                registry.slots[slot] = ParticipantSlot::default();
+            }
+        }
+
+        let clock = Clock::get()?;
+        let sequence = next_sequence(channel);
+        emit!(ParticipantLeft {
+            channel: channel.key(),
+            participant: participant.participant,
+            sequence,
+            timestamp: clock.unix_timestamp,
+        });
+
         msg!(
             "User {:?} left channel {:?}",
             participant.participant,
@@ -933,7 +2637,7 @@ pub mod pod_com {
         _nonce: u64,
     ) -> Result<()> {
         let participant = &ctx.accounts.participant_account;
-        let channel = &ctx.accounts.channel_account;
+        let channel = &mut ctx.accounts.channel_account;
         let message = &mut ctx.accounts.message_account;
         let clock = Clock::get()?;
 
@@ -947,6 +2651,15 @@ pub mod pod_com {
             return Err(PodComError::NotInChannel.into());
         }
 
+        // If a registry is attached, cross-check membership in it too rather
+        // than trusting the passed-in `ChannelParticipant` PDA alone.
+        if let Some(registry) = &ctx.accounts.participant_registry {
+            match registry_find(registry, &participant.participant) {
+                Some(slot) if registry.slots[slot].flags & 1 != 0 => {}
+                _ => return Err(PodComError::NotInChannel.into()),
+            }
+        }
+
         // SECURITY ENHANCEMENT (MED-02): Advanced sliding window rate limiting with burst protection
         let current_time = clock.unix_timestamp;
         let time_window = 60; // 1 minute window
@@ -998,18 +2711,138 @@ pub mod pod_com {
         // Update timestamp for next rate limit calculation
         participant.last_message_at = current_time;
 
-        // Initialize message
-        message.channel = channel.key();
-        // IMPORTANT: Use agent PDA as sender for consistency across all message types
-        // This ensures all messages are associated with registered agents, not raw wallets
-        message.sender = participant.participant; // This is the agent PDA
-        message.content = content;
+        // Charge the configured per-message fee, moving real funds out of the
+        // sender's escrow the same way DepositEscrow/WithdrawEscrow do
+        // (lamport transfer or SPL token::transfer, branching on fee_mint),
+        // rather than the ledger-only reclassification join_channel uses.
+        if channel.fee_per_message > 0 {
+            let fee = channel.fee_per_message;
+            let escrow = ctx
+                .accounts
+                .escrow_account
+                .as_mut()
+                .ok_or(PodComError::InsufficientFunds)?;
+
+            if escrow.depositor != ctx.accounts.user.key() {
+                return Err(PodComError::Unauthorized.into());
+            }
+            if escrow.amount < fee {
+                return Err(PodComError::InsufficientFunds.into());
+            }
+
+            if channel.fee_mint != Pubkey::default() {
+                let vault = ctx
+                    .accounts
+                    .escrow_token_vault
+                    .as_ref()
+                    .ok_or(PodComError::MissingTokenAccounts)?;
+                let creator_token_account = ctx
+                    .accounts
+                    .creator_token_account
+                    .as_ref()
+                    .ok_or(PodComError::MissingTokenAccounts)?;
+                let token_program = ctx
+                    .accounts
+                    .token_program
+                    .as_ref()
+                    .ok_or(PodComError::MissingTokenAccounts)?;
+
+                // SECURITY: pin the fee destination the same way deposit_escrow
+                // pins the source - mint must match the channel's fee_mint and
+                // the account must actually belong to the channel creator, or
+                // `user` could redirect the fee to an arbitrary token account.
+                if creator_token_account.mint != channel.fee_mint {
+                    return Err(PodComError::FeeMintMismatch.into());
+                }
+                if creator_token_account.owner != channel.creator {
+                    return Err(PodComError::Unauthorized.into());
+                }
+
+                let channel_key = channel.key();
+                let depositor_key = ctx.accounts.user.key();
+                let escrow_bump = escrow.bump;
+                let escrow_seeds: &[&[u8]] = &[
+                    b"escrow",
+                    channel_key.as_ref(),
+                    depositor_key.as_ref(),
+                    &[escrow_bump],
+                ];
+
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        token_program.to_account_info(),
+                        Transfer {
+                            from: vault.to_account_info(),
+                            to: creator_token_account.to_account_info(),
+                            authority: escrow.to_account_info(),
+                        },
+                        &[escrow_seeds],
+                    ),
+                    fee,
+                )?;
+            } else {
+                let creator_info = ctx
+                    .accounts
+                    .creator
+                    .as_ref()
+                    .ok_or(PodComError::InsufficientFunds)?
+                    .to_account_info();
+                if creator_info.key() != channel.creator {
+                    return Err(PodComError::Unauthorized.into());
+                }
+
+                let escrow_info = escrow.to_account_info();
+                let new_escrow_lamports = escrow_info
+                    .lamports()
+                    .checked_sub(fee)
+                    .ok_or(PodComError::LamportArithmeticOverflow)?;
+                let new_creator_lamports = creator_info
+                    .lamports()
+                    .checked_add(fee)
+                    .ok_or(PodComError::LamportArithmeticOverflow)?;
+                **escrow_info.try_borrow_mut_lamports()? = new_escrow_lamports;
+                **creator_info.try_borrow_mut_lamports()? = new_creator_lamports;
+            }
+
+            escrow.amount = escrow
+                .amount
+                .checked_sub(fee)
+                .ok_or(PodComError::InsufficientFunds)?;
+            // Keep the channel-wide total in sync with the real funds moved
+            // out of escrow, same as splice_channel's splice-out, so
+            // close_channel's settlement-total check still matches reality.
+            channel.escrow_balance = channel
+                .escrow_balance
+                .checked_sub(fee)
+                .ok_or(PodComError::InsufficientFunds)?;
+        }
+
+        let content_hash = anchor_lang::solana_program::keccak::hash(content.as_bytes()).0;
+
+        // Initialize message
+        message.channel = channel.key();
+        // IMPORTANT: Use agent PDA as sender for consistency across all message types
+        // This ensures all messages are associated with registered agents, not raw wallets
+        message.sender = participant.participant; // This is the agent PDA
+        message.content = content;
+        message.extension = Vec::new();
         message.message_type = message_type;
         message.created_at = clock.unix_timestamp;
         message.edited_at = None;
         message.reply_to = reply_to;
         message.bump = ctx.bumps.message_account;
 
+        let sequence = next_sequence(channel);
+        emit!(MessageBroadcast {
+            channel: channel.key(),
+            sender: message.sender,
+            message_type,
+            content_hash,
+            reply_to,
+            sequence,
+            timestamp: clock.unix_timestamp,
+        });
+
         msg!("Message broadcast to channel {:?}", channel.name);
         Ok(())
     }
@@ -1017,7 +2850,7 @@ pub mod pod_com {
     // Invite user to private channel with cryptographic security
     // SECURITY ENHANCEMENT (MED-01): Cryptographically secure single-use invitations
     pub fn invite_to_channel(ctx: Context<InviteToChannel>, invitee: Pubkey, nonce: u64) -> Result<()> {
-        let channel = &ctx.accounts.channel_account;
+        let channel = &mut ctx.accounts.channel_account;
         let invitation = &mut ctx.accounts.invitation_account;
         let clock = Clock::get()?;
 
@@ -1056,6 +2889,15 @@ pub mod pod_com {
         invitation.nonce = nonce;
         invitation.bump = ctx.bumps.invitation_account;
 
+        let sequence = next_sequence(channel);
+        emit!(InvitationSent {
+            channel: channel.key(),
+            inviter: invitation.inviter,
+            invitee,
+            sequence,
+            timestamp: clock.unix_timestamp,
+        });
+
         msg!(
             "Secure invitation sent to {:?} for channel {:?} with hash {:?}",
             invitee,
@@ -1065,21 +2907,151 @@ pub mod pod_com {
         Ok(())
     }
 
+    // Create a reusable, signed channel offer (BOLT12-style). Unlike
+    // `invite_to_channel`, which mints one single-use invitation per
+    // invitee, an offer can be redeemed by any number of agents up to an
+    // optional cap, which is cheaper when onboarding many agents at once.
+    pub fn create_channel_offer(
+        ctx: Context<CreateChannelOffer>,
+        nonce: u64,
+        max_redemptions: Option<u32>,
+        expires_at: i64,
+    ) -> Result<()> {
+        let channel = &mut ctx.accounts.channel_account;
+        let offer = &mut ctx.accounts.offer_account;
+        let clock = Clock::get()?;
+
+        // Only creator or existing participants can issue offers (same
+        // authorization as the single-use invitation path).
+        if ctx.accounts.issuer.key() != channel.creator {
+            if let Some(participant) = &ctx.accounts.participant_account {
+                if !participant.is_active {
+                    return Err(PodComError::Unauthorized.into());
+                }
+            } else {
+                return Err(PodComError::Unauthorized.into());
+            }
+        }
+
+        if expires_at <= clock.unix_timestamp {
+            return Err(PodComError::MessageExpired.into());
+        }
+
+        let mut hash_input = Vec::new();
+        hash_input.extend_from_slice(&channel.key().to_bytes());
+        hash_input.extend_from_slice(&ctx.accounts.issuer.key().to_bytes());
+        hash_input.extend_from_slice(&nonce.to_le_bytes());
+        let offer_hash = anchor_lang::solana_program::keccak::hash(&hash_input);
+
+        offer.channel = channel.key();
+        offer.issuer = ctx.accounts.issuer.key();
+        offer.offer_hash = offer_hash.to_bytes();
+        offer.nonce = nonce;
+        offer.created_at = clock.unix_timestamp;
+        offer.expires_at = expires_at;
+        offer.max_redemptions = max_redemptions;
+        offer.redemptions_used = 0;
+        offer.is_active = true;
+        offer.bump = ctx.bumps.offer_account;
+
+        let sequence = next_sequence(channel);
+        emit!(ChannelOfferCreated {
+            channel: channel.key(),
+            issuer: offer.issuer,
+            offer: offer.key(),
+            max_redemptions,
+            expires_at,
+            sequence,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Channel offer created for {:?}", channel.name);
+        Ok(())
+    }
+
+    // Redeem a channel offer to join its (typically private) channel
+    // directly, without a pre-issued invitation.
+    pub fn redeem_offer(ctx: Context<RedeemOffer>) -> Result<()> {
+        let channel = &mut ctx.accounts.channel_account;
+        let offer = &mut ctx.accounts.offer_account;
+        let participant = &mut ctx.accounts.participant_account;
+        let clock = Clock::get()?;
+
+        if channel.is_closing {
+            return Err(PodComError::ChannelClosing.into());
+        }
+        if !offer.is_active {
+            return Err(PodComError::OfferInactive.into());
+        }
+        if clock.unix_timestamp > offer.expires_at {
+            return Err(PodComError::OfferExpired.into());
+        }
+        if let Some(max) = offer.max_redemptions {
+            if offer.redemptions_used >= max {
+                return Err(PodComError::OfferRedemptionCapReached.into());
+            }
+        }
+        if channel.current_participants >= channel.max_participants {
+            return Err(PodComError::ChannelFull.into());
+        }
+
+        offer.redemptions_used = offer
+            .redemptions_used
+            .checked_add(1)
+            .ok_or(PodComError::OfferRedemptionCapReached)?;
+
+        participant.channel = channel.key();
+        participant.participant = ctx.accounts.agent_account.key();
+        participant.joined_at = clock.unix_timestamp;
+        participant.is_active = true;
+        participant.messages_sent = 0;
+        participant.last_message_at = 0;
+        participant.bump = ctx.bumps.participant_account;
+
+        channel.current_participants += 1;
+
+        let sequence = next_sequence(channel);
+        emit!(OfferRedeemed {
+            channel: channel.key(),
+            offer: offer.key(),
+            redeemer: participant.participant,
+            redemptions_used: offer.redemptions_used,
+            sequence,
+            timestamp: clock.unix_timestamp,
+        });
+        let sequence = next_sequence(channel);
+        emit!(ParticipantJoined {
+            channel: channel.key(),
+            participant: participant.participant,
+            sequence,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Offer redeemed for channel {:?}", channel.name);
+        Ok(())
+    }
+
     // Get channel participants (view function - would be called off-chain)
     pub fn get_channel_participants(ctx: Context<GetChannelParticipants>) -> Result<Vec<Pubkey>> {
-        // Note: In Solana programs, this function returns empty as participant data
-        // is typically queried off-chain via getProgramAccounts RPC calls for efficiency.
-        // The channel account stores the current participant count, but individual
-        // participant pubkeys are stored in separate ChannelParticipant accounts.
-
         // For on-chain validation, we verify the channel exists and is active
         let channel = &ctx.accounts.channel_account;
         require!(channel.is_active, PodComError::NotInChannel);
 
-        // Return empty vector as participant enumeration is done off-chain
+        // When a `ParticipantRegistry` is attached, membership can actually
+        // be enumerated on-chain from its occupied slots.
+        if let Some(registry) = &ctx.accounts.participant_registry {
+            return Ok((0..PARTICIPANT_REGISTRY_CAPACITY)
+                .filter(|&i| registry.slots[i].flags & 1 != 0)
+                .map(|i| registry.slots[i].agent)
+                .collect());
+        }
+
+        // Without a registry, participant pubkeys live in separate
+        // `ChannelParticipant` PDAs that this instruction cannot enumerate.
         // Off-chain clients should use:
         // - getProgramAccounts with ChannelParticipant discriminator
         // - Filter by channel pubkey and is_active = true
+        // - Or replay `ParticipantJoined` / `ParticipantLeft` events
         Ok(vec![])
     }
 
@@ -1134,6 +3106,136 @@ pub mod pod_com {
         Ok(())
     }
 
+    // Splice: atomically resize a live channel's capacity and/or its
+    // aggregate escrow without tearing it down. A splice-in adds the
+    // depositor's lamports to their own escrow PDA (creating it on first
+    // use); a splice-out returns a portion of it, rejected if doing so would
+    // drop the channel's escrow below its outstanding fee_per_message
+    // obligations. At most one direction may be used per call; with both
+    // amounts zero only max_participants changes.
+    pub fn splice_channel(
+        ctx: Context<SpliceChannel>,
+        new_max_participants: Option<u32>,
+        splice_in_amount: u64,
+        splice_out_amount: u64,
+    ) -> Result<()> {
+        if splice_in_amount > 0 && splice_out_amount > 0 {
+            return Err(PodComError::InvalidSpliceAmount.into());
+        }
+
+        let channel = &mut ctx.accounts.channel_account;
+        let clock = Clock::get()?;
+
+        // Creator-or-participant authorization, mirroring invite_to_channel.
+        if ctx.accounts.depositor.key() != channel.creator {
+            if let Some(participant) = &ctx.accounts.participant_account {
+                if !participant.is_active {
+                    return Err(PodComError::Unauthorized.into());
+                }
+            } else {
+                return Err(PodComError::Unauthorized.into());
+            }
+        }
+
+        let max_participants_before = channel.max_participants;
+        let escrow_balance_before = channel.escrow_balance;
+
+        if let Some(new_max) = new_max_participants {
+            if new_max < channel.current_participants {
+                return Err(PodComError::ChannelFull.into());
+            }
+            channel.max_participants = new_max;
+        }
+
+        let escrow = &mut ctx.accounts.escrow_account;
+
+        if splice_in_amount > 0 {
+            let transfer_instruction = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.depositor.key(),
+                &escrow.key(),
+                splice_in_amount,
+            );
+            anchor_lang::solana_program::program::invoke(
+                &transfer_instruction,
+                &[
+                    ctx.accounts.depositor.to_account_info(),
+                    escrow.to_account_info(),
+                ],
+            )?;
+
+            // Initialize a freshly-created escrow PDA.
+            if escrow.channel == Pubkey::default() {
+                escrow.channel = channel.key();
+                escrow.depositor = ctx.accounts.depositor.key();
+                escrow.created_at = clock.unix_timestamp;
+                escrow.bump = ctx.bumps.escrow_account;
+            }
+
+            escrow.amount = escrow
+                .amount
+                .checked_add(splice_in_amount)
+                .ok_or(PodComError::InvalidSpliceAmount)?;
+            channel.escrow_balance = channel
+                .escrow_balance
+                .checked_add(splice_in_amount)
+                .ok_or(PodComError::InvalidSpliceAmount)?;
+        }
+
+        if splice_out_amount > 0 {
+            if escrow.amount < splice_out_amount {
+                return Err(PodComError::InsufficientFunds.into());
+            }
+
+            // Pending accounting: approximate outstanding obligations as one
+            // fee_per_message owed per current participant.
+            let outstanding = channel
+                .fee_per_message
+                .checked_mul(channel.current_participants as u64)
+                .ok_or(PodComError::InvalidSpliceAmount)?;
+            let new_balance = channel
+                .escrow_balance
+                .checked_sub(splice_out_amount)
+                .ok_or(PodComError::InsufficientFunds)?;
+            if new_balance < outstanding {
+                return Err(PodComError::SpliceBelowObligations.into());
+            }
+
+            let escrow_info = escrow.to_account_info();
+            let depositor_info = ctx.accounts.depositor.to_account_info();
+            let new_escrow_lamports = escrow_info
+                .lamports()
+                .checked_sub(splice_out_amount)
+                .ok_or(PodComError::LamportArithmeticOverflow)?;
+            let new_depositor_lamports = depositor_info
+                .lamports()
+                .checked_add(splice_out_amount)
+                .ok_or(PodComError::LamportArithmeticOverflow)?;
+            **escrow_info.try_borrow_mut_lamports()? = new_escrow_lamports;
+            **depositor_info.try_borrow_mut_lamports()? = new_depositor_lamports;
+
+            escrow.amount = escrow
+                .amount
+                .checked_sub(splice_out_amount)
+                .ok_or(PodComError::InsufficientFunds)?;
+            channel.escrow_balance = new_balance;
+        }
+
+        let sequence = next_sequence(channel);
+        emit!(SpliceExecuted {
+            channel: channel.key(),
+            depositor: ctx.accounts.depositor.key(),
+            max_participants_before,
+            max_participants_after: channel.max_participants,
+            escrow_balance_before,
+            escrow_balance_after: channel.escrow_balance,
+            sequence,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Channel {:?} spliced", channel.key());
+        Ok(())
+    }
+
     // Enhanced create channel with validation
     pub fn create_channel_v2(
         ctx: Context<CreateChannelV2>,
@@ -1142,6 +3244,7 @@ pub mod pod_com {
         visibility: ChannelVisibility,
         max_participants: u32,
         fee_per_message: u64,
+        fee_mint: Option<Pubkey>,
     ) -> Result<()> {
         let agent = &ctx.accounts.agent_account;
         let channel = &mut ctx.accounts.channel_account;
@@ -1177,6 +3280,16 @@ pub mod pod_com {
         channel.escrow_balance = 0;
         channel.created_at = clock.unix_timestamp;
         channel.is_active = true;
+        channel.is_closing = false;
+        channel.dispute_window_ends_at = 0;
+        channel.settlement_root = [0u8; 32];
+        channel.event_sequence = 0;
+        channel.compression_tree = Pubkey::default();
+        channel.compression_queue = Pubkey::default();
+        channel.compression_root = [0u8; 32];
+        channel.compression_capacity = 0;
+        channel.compression_leaf_count = 0;
+        channel.fee_mint = fee_mint.unwrap_or_default();
         channel.bump = ctx.bumps.channel_account;
 
         // Add creator as first participant
@@ -1188,6 +3301,16 @@ pub mod pod_com {
         participant.last_message_at = 0;
         participant.bump = ctx.bumps.participant_account;
 
+        let sequence = next_sequence(channel);
+        emit!(ChannelCreated {
+            channel: channel.key(),
+            creator: channel.creator,
+            name: channel.name.clone(),
+            visibility: channel.visibility,
+            sequence,
+            timestamp: channel.created_at,
+        });
+
         msg!("Enhanced channel created: {:?}", channel.name);
         Ok(())
     }
@@ -1226,7 +3349,7 @@ pub mod pod_com {
         ipfs_hash: String,
     ) -> Result<()> {
         let participant = &ctx.accounts.participant_account;
-        let channel = &ctx.accounts.channel_account;
+        let channel = &mut ctx.accounts.channel_account;
         let clock = Clock::get()?;
 
         // SECURITY CHECKS (CRIT-01): Comprehensive validation for ZK compression
@@ -1245,7 +3368,14 @@ pub mod pod_com {
         if !participant.is_active {
             return Err(PodComError::NotInChannel.into());
         }
-        
+
+        // Feature negotiation: the sender's mandatory features must be
+        // understood by the channel owner's agent before the broadcast lands.
+        verify_feature_compatibility(
+            ctx.accounts.sender_agent.capabilities,
+            ctx.accounts.channel_owner_agent.capabilities,
+        )?;
+
         // Verify participant PDA derivation to prevent substitution attacks
         let agent_account = &ctx.accounts.participant_account;
         let (expected_participant_pda, _bump) = Pubkey::find_program_address(
@@ -1288,8 +3418,37 @@ pub mod pod_com {
         // Create content hash using secure memory and Light Protocol's Poseidon hasher
         let content_hash = secure_hash_data(content.as_bytes())?;
 
-        // Create compressed message data (temporarily stored as regular account data)
-        let _compressed_message = CompressedChannelMessage {
+        let negotiated = negotiate_features(
+            ctx.accounts.sender_agent.capabilities,
+            ctx.accounts.channel_owner_agent.capabilities,
+        );
+        emit!(FeaturesNegotiated {
+            agent_a: ctx.accounts.sender_agent.key(),
+            agent_b: ctx.accounts.channel_owner_agent.key(),
+            negotiated,
+            timestamp: clock.unix_timestamp,
+        });
+
+        // Channel must have a registered Merkle tree / nullifier queue pair;
+        // that the caller's accounts are exactly that pair is already
+        // enforced by the `merkle_tree`/`nullifier_queue` constraints on
+        // `BroadcastMessageCompressed`.
+        if channel.compression_tree == Pubkey::default() {
+            return Err(PodComError::CompressionNotConfigured.into());
+        }
+        if channel.is_closing {
+            return Err(PodComError::ChannelClosing.into());
+        }
+        // This program cannot verify the real account-compression queue's
+        // remaining capacity against its actual IDL in this environment, so
+        // it enforces the program-controlled ceiling set by
+        // configure_channel_compression instead of relying solely on the
+        // CPI failing once the on-chain queue is full.
+        if channel.compression_leaf_count >= channel.compression_capacity {
+            return Err(PodComError::CompressionQueueFull.into());
+        }
+
+        let compressed_message = CompressedChannelMessage {
             channel: channel.key(),
             sender: participant.participant,
             content_hash,
@@ -1298,17 +3457,42 @@ pub mod pod_com {
             created_at: clock.unix_timestamp,
             edited_at: None,
             reply_to,
+            extension: Vec::new(),
         };
-
-        // TODO: Implement actual compression using updated Light Protocol API
-        // let compressed_account_data = borsh::to_vec(&compressed_message)?;
-        // Temporarily disabled compression functionality
+        let leaf = compressed_message.hash::<Poseidon>().map_err(|_| PodComError::Unauthorized)?;
+
+        let leaf_index = cpi_append_compressed_leaf(
+            &ctx.accounts.account_compression_program,
+            &ctx.accounts.registered_program_id,
+            &ctx.accounts.noop_program,
+            &ctx.accounts.account_compression_authority,
+            &ctx.accounts.merkle_tree,
+            &ctx.accounts.nullifier_queue,
+            &ctx.accounts.cpi_authority_pda,
+            ctx.bumps.cpi_authority_pda,
+            leaf,
+        )?;
+        channel.compression_leaf_count = channel
+            .compression_leaf_count
+            .checked_add(1)
+            .ok_or(PodComError::CompressionQueueFull)?;
 
         // Emit event for indexing
+        let sequence = next_sequence(channel);
         emit!(MessageBroadcast {
             channel: channel.key(),
             sender: participant.participant,
             message_type,
+            content_hash,
+            reply_to,
+            sequence,
+            timestamp: clock.unix_timestamp,
+        });
+        emit!(CompressedLeafAppended {
+            channel: channel.key(),
+            merkle_tree: channel.compression_tree,
+            leaf,
+            leaf_index,
             timestamp: clock.unix_timestamp,
         });
 
@@ -1358,8 +3542,24 @@ pub mod pod_com {
             }
         }
 
-        // Use provided metadata_hash for participant compression
-        let metadata_hash = metadata_hash;
+        // Channel must have a registered Merkle tree / nullifier queue pair;
+        // that the caller's accounts are exactly that pair is already
+        // enforced by the `merkle_tree`/`nullifier_queue` constraints on
+        // `JoinChannelCompressed`.
+        if channel.compression_tree == Pubkey::default() {
+            return Err(PodComError::CompressionNotConfigured.into());
+        }
+        if channel.is_closing {
+            return Err(PodComError::ChannelClosing.into());
+        }
+        // This program cannot verify the real account-compression queue's
+        // remaining capacity against its actual IDL in this environment, so
+        // it enforces the program-controlled ceiling set by
+        // configure_channel_compression instead of relying solely on the
+        // CPI failing once the on-chain queue is full.
+        if channel.compression_leaf_count >= channel.compression_capacity {
+            return Err(PodComError::CompressionQueueFull.into());
+        }
 
         let compressed_participant = CompressedChannelParticipant {
             channel: channel.key(),
@@ -1369,20 +3569,42 @@ pub mod pod_com {
             last_message_at: 0,
             metadata_hash,
         };
-
-        // Compress the participant account
-        let _compressed_account_data = borsh::to_vec(&compressed_participant)?;
-
-        // TODO: Re-implement Light Protocol compression for channel joining
-        // Temporarily disabled compression functionality
+        let leaf = compressed_participant
+            .hash::<Poseidon>()
+            .map_err(|_| PodComError::Unauthorized)?;
+
+        let leaf_index = cpi_append_compressed_leaf(
+            &ctx.accounts.account_compression_program,
+            &ctx.accounts.registered_program_id,
+            &ctx.accounts.noop_program,
+            &ctx.accounts.account_compression_authority,
+            &ctx.accounts.merkle_tree,
+            &ctx.accounts.nullifier_queue,
+            &ctx.accounts.cpi_authority_pda,
+            ctx.bumps.cpi_authority_pda,
+            leaf,
+        )?;
+        channel.compression_leaf_count = channel
+            .compression_leaf_count
+            .checked_add(1)
+            .ok_or(PodComError::CompressionQueueFull)?;
 
         // Update channel participant count
         channel.current_participants += 1;
 
         // Emit event
+        let sequence = next_sequence(channel);
         emit!(ChannelJoined {
             channel: channel.key(),
             participant: agent.key(),
+            sequence,
+            timestamp: clock.unix_timestamp,
+        });
+        emit!(CompressedLeafAppended {
+            channel: channel.key(),
+            merkle_tree: channel.compression_tree,
+            leaf,
+            leaf_index,
             timestamp: clock.unix_timestamp,
         });
 
@@ -1393,14 +3615,14 @@ pub mod pod_com {
     /// Batch sync compressed messages - periodically sync state to chain
     pub fn batch_sync_compressed_messages(
         ctx: Context<BatchSyncCompressedMessages>,
-        message_hashes: Vec<[u8; 32]>,
+        proofs: Vec<MerkleProofEntry>,
         sync_timestamp: i64,
     ) -> Result<()> {
         let channel = &mut ctx.accounts.channel_account;
         let _clock = Clock::get()?;
 
         // Validate batch size (prevent spam)
-        if message_hashes.len() > 100 {
+        if proofs.len() > 100 {
             return Err(PodComError::RateLimitExceeded.into());
         }
 
@@ -1409,20 +3631,106 @@ pub mod pod_com {
             return Err(PodComError::Unauthorized.into());
         }
 
-        // TODO: Re-implement Light Protocol batch compression
-        // Create batch sync proof using Light Protocol's batch compression
-        for (_i, _hash) in message_hashes.iter().enumerate() {
-            // TODO: Each hash represents a compressed message that was stored off-chain
-            // TODO: Verify the hash and create compressed account using updated API
+        if channel.compression_tree == Pubkey::default() {
+            return Err(PodComError::CompressionNotConfigured.into());
+        }
+
+        // Every entry must independently fold up to the channel's stored
+        // root, so the whole batch is rejected if even one is forged.
+        for proof in proofs.iter() {
+            let root = fold_merkle_proof(proof)?;
+            if root != channel.compression_root {
+                return Err(PodComError::InvalidMerkleProof.into());
+            }
         }
 
         msg!(
             "Batch synced {} compressed messages at timestamp: {}",
-            message_hashes.len(),
+            proofs.len(),
             sync_timestamp
         );
         Ok(())
     }
+
+    /// Reconstruct a full on-chain `DecompressedChannelMessage` from a
+    /// compressed leaf the caller proves was actually appended, then
+    /// nullify that leaf so it cannot be decompressed a second time.
+    #[allow(clippy::too_many_arguments)]
+    pub fn decompress_channel_message(
+        ctx: Context<DecompressChannelMessage>,
+        sender: Pubkey,
+        content_hash: [u8; 32],
+        ipfs_hash: String,
+        message_type: MessageType,
+        created_at: i64,
+        edited_at: Option<i64>,
+        reply_to: Option<Pubkey>,
+        _nonce: u64,
+        leaf_index: u64,
+        siblings: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let channel = &mut ctx.accounts.channel_account;
+
+        if channel.compression_tree == Pubkey::default() {
+            return Err(PodComError::CompressionNotConfigured.into());
+        }
+
+        let compressed_message = CompressedChannelMessage {
+            channel: channel.key(),
+            sender,
+            content_hash,
+            ipfs_hash: ipfs_hash.clone(),
+            message_type,
+            created_at,
+            edited_at,
+            reply_to,
+            extension: Vec::new(),
+        };
+        let leaf = compressed_message.hash::<Poseidon>().map_err(|_| PodComError::Unauthorized)?;
+
+        let proof = MerkleProofEntry { leaf, leaf_index, siblings };
+        if fold_merkle_proof(&proof)? != channel.compression_root {
+            return Err(PodComError::InvalidMerkleProof.into());
+        }
+
+        cpi_nullify_compressed_leaf(
+            &ctx.accounts.account_compression_program,
+            &ctx.accounts.registered_program_id,
+            &ctx.accounts.account_compression_authority,
+            &ctx.accounts.merkle_tree,
+            &ctx.accounts.nullifier_queue,
+            &ctx.accounts.cpi_authority_pda,
+            ctx.bumps.cpi_authority_pda,
+            leaf,
+            leaf_index,
+        )?;
+
+        let decompressed = &mut ctx.accounts.decompressed_message;
+        decompressed.channel = channel.key();
+        decompressed.sender = sender;
+        decompressed.content_hash = content_hash;
+        decompressed.ipfs_hash = ipfs_hash;
+        decompressed.message_type = message_type;
+        decompressed.created_at = created_at;
+        decompressed.edited_at = edited_at;
+        decompressed.reply_to = reply_to;
+        decompressed.bump = ctx.bumps.decompressed_message;
+
+        let clock = Clock::get()?;
+        let sequence = next_sequence(channel);
+        emit!(MessageDecompressed {
+            channel: channel.key(),
+            sender,
+            content_hash,
+            leaf,
+            leaf_index,
+            sequence,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Decompressed message for channel {:?}", channel.name);
+        Ok(())
+    }
 }
 
 // Contexts
@@ -1466,6 +3774,10 @@ pub struct SendMessage<'info> {
         constraint = signer.key() == sender_agent.pubkey @ PodComError::Unauthorized,
     )]
     pub sender_agent: Account<'info, AgentAccount>,
+    #[account(
+        constraint = recipient_agent.key() == recipient @ PodComError::Unauthorized,
+    )]
+    pub recipient_agent: Account<'info, AgentAccount>,
     #[account(mut)]
     pub signer: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -1482,6 +3794,87 @@ pub struct UpdateAgent<'info> {
     pub signer: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct AnnounceAgent<'info> {
+    #[account(
+        seeds = [b"agent", agent_account.pubkey.as_ref()],
+        bump = agent_account.bump,
+        constraint = signer.key() == agent_account.pubkey @ PodComError::Unauthorized,
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = AGENT_ANNOUNCEMENT_SPACE,
+        seeds = [b"announcement", agent_account.key().as_ref()],
+        bump
+    )]
+    pub agent_announcement: Account<'info, AgentAnnouncement>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReestablishSession<'info> {
+    #[account(
+        seeds = [b"agent", agent_account.pubkey.as_ref()],
+        bump = agent_account.bump,
+        constraint = signer.key() == agent_account.pubkey @ PodComError::Unauthorized,
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    pub peer_agent_account: Account<'info, AgentAccount>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = MESSAGE_SESSION_SPACE,
+        seeds = [
+            b"session",
+            min_pubkey(&agent_account.key(), &peer_agent_account.key()).as_ref(),
+            max_pubkey(&agent_account.key(), &peer_agent_account.key()).as_ref(),
+        ],
+        bump
+    )]
+    pub session: Account<'info, MessageSession>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SendHeartbeat<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_account.pubkey.as_ref()],
+        bump = agent_account.bump,
+        constraint = signer.key() == agent_account.pubkey @ PodComError::Unauthorized,
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(target_agent: Option<Pubkey>, target_channel: Option<Pubkey>, data: String, nonce: u64)]
+pub struct SendErrorMessage<'info> {
+    #[account(
+        seeds = [b"agent", sender.key().as_ref()],
+        bump = sender_agent.bump,
+        constraint = sender.key() == sender_agent.pubkey @ PodComError::Unauthorized,
+    )]
+    pub sender_agent: Account<'info, AgentAccount>,
+    #[account(
+        init,
+        payer = sender,
+        space = ERROR_MESSAGE_SPACE,
+        seeds = [b"error", sender_agent.key().as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub error_message: Account<'info, ErrorMessage>,
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateMessageStatus<'info> {
     #[account(
@@ -1511,7 +3904,7 @@ pub struct UpdateMessageStatus<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(name: String, description: String, visibility: ChannelVisibility, max_participants: u32, fee_per_message: u64)]
+#[instruction(name: String, description: String, visibility: ChannelVisibility, max_participants: u32, fee_per_message: u64, fee_mint: Option<Pubkey>)]
 pub struct CreateChannel<'info> {
     #[account(
         init,
@@ -1542,6 +3935,22 @@ pub struct DepositEscrow<'info> {
     #[account(mut)]
     pub depositor: Signer<'info>,
     pub system_program: Program<'info, System>,
+    // SPL-token escrow path: present only when channel_account.fee_mint != default.
+    // escrow_token_vault is PDA-owned (authority = escrow_account) so withdraw_escrow
+    // can move funds back out by signing with the escrow PDA's own seeds.
+    pub token_mint: Option<Account<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        seeds = [b"escrow_vault", channel_account.key().as_ref(), depositor.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = escrow_account,
+    )]
+    pub escrow_token_vault: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub depositor_token_account: Option<Account<'info, TokenAccount>>,
+    pub token_program: Option<Program<'info, Token>>,
 }
 
 #[derive(Accounts)]
@@ -1556,13 +3965,99 @@ pub struct WithdrawEscrow<'info> {
     pub channel_account: Account<'info, ChannelAccount>,
     #[account(mut)]
     pub depositor: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"escrow_vault", channel_account.key().as_ref(), depositor.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_vault: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub depositor_token_account: Option<Account<'info, TokenAccount>>,
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+#[derive(Accounts)]
+#[instruction(payee: Pubkey, amount: u64, hash_lock: [u8; 32], timeout: i64)]
+pub struct CreateHtlc<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = HTLC_ACCOUNT_SPACE,
+        seeds = [b"htlc", payer.key().as_ref(), &hash_lock, &timeout.to_le_bytes()],
+        bump
+    )]
+    pub htlc_account: Account<'info, HtlcAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimHtlc<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"htlc",
+            htlc_account.payer.as_ref(),
+            &htlc_account.hash_lock,
+            &htlc_account.timeout.to_le_bytes(),
+        ],
+        bump = htlc_account.bump,
+    )]
+    pub htlc_account: Account<'info, HtlcAccount>,
+    /// CHECK: validated against `htlc_account.payee`; funds are only ever moved to this address
+    #[account(mut, constraint = payee.key() == htlc_account.payee @ PodComError::Unauthorized)]
+    pub payee: AccountInfo<'info>,
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RefundHtlc<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"htlc",
+            htlc_account.payer.as_ref(),
+            &htlc_account.hash_lock,
+            &htlc_account.timeout.to_le_bytes(),
+        ],
+        bump = htlc_account.bump,
+    )]
+    pub htlc_account: Account<'info, HtlcAccount>,
+    #[account(mut, constraint = payer.key() == htlc_account.payer @ PodComError::Unauthorized)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseChannel<'info> {
+    #[account(
+        mut,
+        constraint = channel_account.creator == creator.key() @ PodComError::Unauthorized
+    )]
+    pub channel_account: Account<'info, ChannelAccount>,
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeClose<'info> {
+    #[account(
+        mut,
+        constraint = channel_account.creator == creator.key() @ PodComError::Unauthorized,
+        close = creator
+    )]
+    pub channel_account: Account<'info, ChannelAccount>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
 }
 
 // New context structures for enhanced functionality
 
 #[derive(Accounts)]
 pub struct JoinChannel<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = !channel_account.is_closing @ PodComError::ChannelClosing
+    )]
     pub channel_account: Account<'info, ChannelAccount>,
     #[account(
         init,
@@ -1590,6 +4085,12 @@ pub struct JoinChannel<'info> {
         bump
     )]
     pub escrow_account: Option<Account<'info, EscrowAccount>>,
+    #[account(
+        mut,
+        seeds = [b"registry", channel_account.key().as_ref()],
+        bump = participant_registry.bump,
+    )]
+    pub participant_registry: Option<Account<'info, ParticipantRegistry>>,
     #[account(mut)]
     pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -1612,16 +4113,64 @@ pub struct LeaveChannel<'info> {
         constraint = user.key() == agent_account.pubkey @ PodComError::Unauthorized,
     )]
     pub agent_account: Account<'info, AgentAccount>,
+    #[account(
+        mut,
+        seeds = [b"registry", channel_account.key().as_ref()],
+        bump = participant_registry.bump,
+    )]
+    pub participant_registry: Option<Account<'info, ParticipantRegistry>>,
     #[account(mut)]
     pub user: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct CreateParticipantRegistry<'info> {
+    #[account(
+        constraint = channel_account.creator == creator.key() @ PodComError::Unauthorized
+    )]
+    pub channel_account: Account<'info, ChannelAccount>,
+    #[account(
+        init,
+        payer = creator,
+        space = PARTICIPANT_REGISTRY_SPACE,
+        seeds = [b"registry", channel_account.key().as_ref()],
+        bump
+    )]
+    pub participant_registry: Account<'info, ParticipantRegistry>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(merkle_tree: Pubkey, nullifier_queue: Pubkey)]
+pub struct ConfigureChannelCompression<'info> {
+    #[account(
+        mut,
+        constraint = channel_account.creator == creator.key() @ PodComError::Unauthorized
+    )]
+    pub channel_account: Account<'info, ChannelAccount>,
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_root: [u8; 32])]
+pub struct UpdateCompressionRoot<'info> {
+    #[account(
+        mut,
+        constraint = channel_account.creator == creator.key() @ PodComError::Unauthorized
+    )]
+    pub channel_account: Account<'info, ChannelAccount>,
+    pub creator: Signer<'info>,
+}
+
 #[derive(Accounts)]
 #[instruction(content: String, message_type: MessageType, reply_to: Option<Pubkey>, nonce: u64)]
 pub struct BroadcastMessage<'info> {
     #[account(
         mut,
-        constraint = channel_account.is_active @ PodComError::Unauthorized
+        constraint = channel_account.is_active @ PodComError::Unauthorized,
+        constraint = !channel_account.is_closing @ PodComError::ChannelClosing
     )]
     pub channel_account: Account<'info, ChannelAccount>,
     #[account(
@@ -1650,14 +4199,42 @@ pub struct BroadcastMessage<'info> {
         bump
     )]
     pub message_account: Account<'info, ChannelMessage>,
+    #[account(
+        seeds = [b"registry", channel_account.key().as_ref()],
+        bump = participant_registry.bump,
+    )]
+    pub participant_registry: Option<Account<'info, ParticipantRegistry>>,
     #[account(mut)]
     pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
+    // Per-message fee path: present only when channel_account.fee_per_message
+    // > 0. Mirrors the escrow deposit/withdraw accounts so the fee moves the
+    // same way a deposit/withdraw would, branching on fee_mint for SPL-token
+    // vs native-lamport channels.
+    #[account(
+        mut,
+        seeds = [b"escrow", channel_account.key().as_ref(), user.key().as_ref()],
+        bump = escrow_account.bump,
+    )]
+    pub escrow_account: Option<Account<'info, EscrowAccount>>,
+    /// CHECK: lamport fee destination, verified against channel_account.creator in the handler.
+    #[account(mut)]
+    pub creator: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        seeds = [b"escrow_vault", channel_account.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub escrow_token_vault: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub creator_token_account: Option<Account<'info, TokenAccount>>,
+    pub token_program: Option<Program<'info, Token>>,
 }
 
 #[derive(Accounts)]
 #[instruction(invitee: Pubkey, nonce: u64)]
 pub struct InviteToChannel<'info> {
+    #[account(mut)]
     pub channel_account: Account<'info, ChannelAccount>,
     #[account(
         seeds = [b"participant", channel_account.key().as_ref(), agent_account.key().as_ref()],
@@ -1684,9 +4261,74 @@ pub struct InviteToChannel<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct CreateChannelOffer<'info> {
+    #[account(mut)]
+    pub channel_account: Account<'info, ChannelAccount>,
+    #[account(
+        seeds = [b"participant", channel_account.key().as_ref(), agent_account.key().as_ref()],
+        bump = participant_account.bump,
+        constraint = participant_account.is_active @ PodComError::NotInChannel
+    )]
+    pub participant_account: Option<Account<'info, ChannelParticipant>>,
+    #[account(
+        seeds = [b"agent", issuer.key().as_ref()],
+        bump = agent_account.bump,
+        constraint = issuer.key() == agent_account.pubkey @ PodComError::Unauthorized,
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    #[account(
+        init,
+        payer = issuer,
+        space = CHANNEL_OFFER_SPACE,
+        seeds = [b"offer", channel_account.key().as_ref(), issuer.key().as_ref(), nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub offer_account: Account<'info, ChannelOffer>,
+    #[account(mut)]
+    pub issuer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemOffer<'info> {
+    #[account(mut)]
+    pub channel_account: Account<'info, ChannelAccount>,
+    #[account(
+        mut,
+        seeds = [b"offer", channel_account.key().as_ref(), offer_account.issuer.as_ref(), offer_account.nonce.to_le_bytes().as_ref()],
+        bump = offer_account.bump,
+        constraint = offer_account.channel == channel_account.key() @ PodComError::Unauthorized,
+    )]
+    pub offer_account: Account<'info, ChannelOffer>,
+    #[account(
+        init,
+        payer = redeemer,
+        space = CHANNEL_PARTICIPANT_SPACE,
+        seeds = [b"participant", channel_account.key().as_ref(), agent_account.key().as_ref()],
+        bump
+    )]
+    pub participant_account: Account<'info, ChannelParticipant>,
+    #[account(
+        seeds = [b"agent", redeemer.key().as_ref()],
+        bump = agent_account.bump,
+        constraint = redeemer.key() == agent_account.pubkey @ PodComError::Unauthorized,
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    #[account(mut)]
+    pub redeemer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct GetChannelParticipants<'info> {
     pub channel_account: Account<'info, ChannelAccount>,
+    #[account(
+        seeds = [b"registry", channel_account.key().as_ref()],
+        bump = participant_registry.bump,
+    )]
+    pub participant_registry: Option<Account<'info, ParticipantRegistry>>,
 }
 
 #[derive(Accounts)]
@@ -1700,7 +4342,38 @@ pub struct UpdateChannel<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(name: String, description: String, visibility: ChannelVisibility, max_participants: u32, fee_per_message: u64)]
+pub struct SpliceChannel<'info> {
+    #[account(
+        mut,
+        constraint = !channel_account.is_closing @ PodComError::ChannelClosing
+    )]
+    pub channel_account: Account<'info, ChannelAccount>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = ESCROW_ACCOUNT_SPACE,
+        seeds = [b"escrow", channel_account.key().as_ref(), depositor.key().as_ref()],
+        bump
+    )]
+    pub escrow_account: Account<'info, EscrowAccount>,
+    #[account(
+        seeds = [b"agent", depositor.key().as_ref()],
+        bump = agent_account.bump,
+        constraint = depositor.key() == agent_account.pubkey @ PodComError::Unauthorized,
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    #[account(
+        seeds = [b"participant", channel_account.key().as_ref(), agent_account.key().as_ref()],
+        bump = participant_account.bump,
+    )]
+    pub participant_account: Option<Account<'info, ChannelParticipant>>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String, description: String, visibility: ChannelVisibility, max_participants: u32, fee_per_message: u64, fee_mint: Option<Pubkey>)]
 pub struct CreateChannelV2<'info> {
     #[account(
         seeds = [b"agent", creator.key().as_ref()],
@@ -1736,9 +4409,19 @@ pub struct CreateChannelV2<'info> {
 #[derive(Accounts)]
 #[instruction(content: String, message_type: MessageType, reply_to: Option<Pubkey>, ipfs_hash: String)]
 pub struct BroadcastMessageCompressed<'info> {
+    #[account(mut)]
     pub channel_account: Account<'info, ChannelAccount>,
     #[account(mut)]
     pub participant_account: Account<'info, ChannelParticipant>,
+    #[account(
+        constraint = sender_agent.key() == participant_account.participant @ PodComError::Unauthorized,
+    )]
+    pub sender_agent: Account<'info, AgentAccount>,
+    #[account(
+        seeds = [b"agent", channel_account.creator.as_ref()],
+        bump = channel_owner_agent.bump,
+    )]
+    pub channel_owner_agent: Account<'info, AgentAccount>,
     #[account(mut)]
     pub fee_payer: Signer<'info>,
     pub authority: Signer<'info>,
@@ -1746,19 +4429,66 @@ pub struct BroadcastMessageCompressed<'info> {
     pub light_system_program: Program<'info, LightSystemProgram>,
     /// CHECK: Compressed Token Program (Light Protocol)
     pub compressed_token_program: Program<'info, LightCompressedToken>,
-    /// CHECK: Registered program PDA
+    /// CHECK: pinned to this deployment's fixed registration PDA below
+    #[account(address = light_protocol_ids::REGISTERED_PROGRAM_ID @ PodComError::InvalidCompressionAccount)]
     pub registered_program_id: AccountInfo<'info>,
-    /// CHECK: Noop program for logging
+    /// CHECK: pinned to the canonical SPL Noop program below
+    #[account(address = light_protocol_ids::NOOP_PROGRAM_ID @ PodComError::InvalidCompressionAccount)]
     pub noop_program: AccountInfo<'info>,
-    /// CHECK: Account compression authority
+    /// CHECK: pinned to the account compression program's global authority PDA below
+    #[account(address = light_protocol_ids::ACCOUNT_COMPRESSION_AUTHORITY_ID @ PodComError::InvalidCompressionAccount)]
+    pub account_compression_authority: AccountInfo<'info>,
+    /// CHECK: pinned to the canonical account compression program below
+    #[account(address = light_protocol_ids::ACCOUNT_COMPRESSION_PROGRAM_ID @ PodComError::InvalidCompressionAccount)]
+    pub account_compression_program: AccountInfo<'info>,
+    /// CHECK: must be the exact tree registered for this channel via configure_channel_compression
+    #[account(constraint = merkle_tree.key() == channel_account.compression_tree @ PodComError::InvalidCompressionAccount)]
+    pub merkle_tree: AccountInfo<'info>,
+    /// CHECK: must be the exact queue paired with merkle_tree for this channel
+    #[account(constraint = nullifier_queue.key() == channel_account.compression_queue @ PodComError::InvalidCompressionAccount)]
+    pub nullifier_queue: AccountInfo<'info>,
+    /// CHECK: this program's own CPI authority PDA; the seeds constraint
+    /// below is what prevents a caller from substituting an authority it
+    /// controls to forge compressed-tree appends.
+    #[account(seeds = [CPI_AUTHORITY_SEED], bump)]
+    pub cpi_authority_pda: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(sender: Pubkey, content_hash: [u8; 32], ipfs_hash: String, message_type: MessageType, created_at: i64, edited_at: Option<i64>, reply_to: Option<Pubkey>, nonce: u64)]
+pub struct DecompressChannelMessage<'info> {
+    #[account(mut)]
+    pub channel_account: Account<'info, ChannelAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = DECOMPRESSED_MESSAGE_SPACE,
+        seeds = [b"channel_message", channel_account.key().as_ref(), sender.as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub decompressed_message: Account<'info, DecompressedChannelMessage>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: pinned to this deployment's fixed registration PDA below
+    #[account(address = light_protocol_ids::REGISTERED_PROGRAM_ID @ PodComError::InvalidCompressionAccount)]
+    pub registered_program_id: AccountInfo<'info>,
+    /// CHECK: pinned to the account compression program's global authority PDA below
+    #[account(address = light_protocol_ids::ACCOUNT_COMPRESSION_AUTHORITY_ID @ PodComError::InvalidCompressionAccount)]
     pub account_compression_authority: AccountInfo<'info>,
-    /// CHECK: Account compression program
+    /// CHECK: pinned to the canonical account compression program below
+    #[account(address = light_protocol_ids::ACCOUNT_COMPRESSION_PROGRAM_ID @ PodComError::InvalidCompressionAccount)]
     pub account_compression_program: AccountInfo<'info>,
-    /// CHECK: Merkle tree account
+    /// CHECK: must be the exact tree registered for this channel via configure_channel_compression
+    #[account(constraint = merkle_tree.key() == channel_account.compression_tree @ PodComError::InvalidCompressionAccount)]
     pub merkle_tree: AccountInfo<'info>,
-    /// CHECK: Nullifier queue account
+    /// CHECK: must be the exact queue paired with merkle_tree for this channel
+    #[account(constraint = nullifier_queue.key() == channel_account.compression_queue @ PodComError::InvalidCompressionAccount)]
     pub nullifier_queue: AccountInfo<'info>,
-    /// CHECK: CPI authority PDA
+    /// CHECK: this program's own CPI authority PDA; the seeds constraint
+    /// below is what prevents a caller from substituting an authority it
+    /// controls to forge a nullification.
+    #[account(seeds = [CPI_AUTHORITY_SEED], bump)]
     pub cpi_authority_pda: AccountInfo<'info>,
 }
 
@@ -1774,24 +4504,33 @@ pub struct JoinChannelCompressed<'info> {
     pub authority: Signer<'info>,
     /// CHECK: Light System Program
     pub light_system_program: Program<'info, LightSystemProgram>,
-    /// CHECK: Registered program PDA
+    /// CHECK: pinned to this deployment's fixed registration PDA below
+    #[account(address = light_protocol_ids::REGISTERED_PROGRAM_ID @ PodComError::InvalidCompressionAccount)]
     pub registered_program_id: AccountInfo<'info>,
-    /// CHECK: Noop program for logging
+    /// CHECK: pinned to the canonical SPL Noop program below
+    #[account(address = light_protocol_ids::NOOP_PROGRAM_ID @ PodComError::InvalidCompressionAccount)]
     pub noop_program: AccountInfo<'info>,
-    /// CHECK: Account compression authority
+    /// CHECK: pinned to the account compression program's global authority PDA below
+    #[account(address = light_protocol_ids::ACCOUNT_COMPRESSION_AUTHORITY_ID @ PodComError::InvalidCompressionAccount)]
     pub account_compression_authority: AccountInfo<'info>,
-    /// CHECK: Account compression program
+    /// CHECK: pinned to the canonical account compression program below
+    #[account(address = light_protocol_ids::ACCOUNT_COMPRESSION_PROGRAM_ID @ PodComError::InvalidCompressionAccount)]
     pub account_compression_program: AccountInfo<'info>,
-    /// CHECK: Merkle tree account
+    /// CHECK: must be the exact tree registered for this channel via configure_channel_compression
+    #[account(constraint = merkle_tree.key() == channel_account.compression_tree @ PodComError::InvalidCompressionAccount)]
     pub merkle_tree: AccountInfo<'info>,
-    /// CHECK: Nullifier queue account
+    /// CHECK: must be the exact queue paired with merkle_tree for this channel
+    #[account(constraint = nullifier_queue.key() == channel_account.compression_queue @ PodComError::InvalidCompressionAccount)]
     pub nullifier_queue: AccountInfo<'info>,
-    /// CHECK: CPI authority PDA
+    /// CHECK: this program's own CPI authority PDA; the seeds constraint
+    /// below is what prevents a caller from substituting an authority it
+    /// controls to forge compressed-tree appends.
+    #[account(seeds = [CPI_AUTHORITY_SEED], bump)]
     pub cpi_authority_pda: AccountInfo<'info>,
 }
 
 #[derive(Accounts)]
-#[instruction(message_hashes: Vec<[u8; 32]>, sync_timestamp: i64)]
+#[instruction(proofs: Vec<MerkleProofEntry>, sync_timestamp: i64)]
 pub struct BatchSyncCompressedMessages<'info> {
     #[account(mut)]
     pub channel_account: Account<'info, ChannelAccount>,
@@ -1802,18 +4541,27 @@ pub struct BatchSyncCompressedMessages<'info> {
     pub light_system_program: Program<'info, LightSystemProgram>,
     /// CHECK: Compressed Token Program (Light Protocol)
     pub compressed_token_program: Program<'info, LightCompressedToken>,
-    /// CHECK: Registered program PDA
+    /// CHECK: pinned to this deployment's fixed registration PDA below
+    #[account(address = light_protocol_ids::REGISTERED_PROGRAM_ID @ PodComError::InvalidCompressionAccount)]
     pub registered_program_id: AccountInfo<'info>,
-    /// CHECK: Noop program for logging
+    /// CHECK: pinned to the canonical SPL Noop program below
+    #[account(address = light_protocol_ids::NOOP_PROGRAM_ID @ PodComError::InvalidCompressionAccount)]
     pub noop_program: AccountInfo<'info>,
-    /// CHECK: Account compression authority
+    /// CHECK: pinned to the account compression program's global authority PDA below
+    #[account(address = light_protocol_ids::ACCOUNT_COMPRESSION_AUTHORITY_ID @ PodComError::InvalidCompressionAccount)]
     pub account_compression_authority: AccountInfo<'info>,
-    /// CHECK: Account compression program
+    /// CHECK: pinned to the canonical account compression program below
+    #[account(address = light_protocol_ids::ACCOUNT_COMPRESSION_PROGRAM_ID @ PodComError::InvalidCompressionAccount)]
     pub account_compression_program: AccountInfo<'info>,
-    /// CHECK: Merkle tree account
+    /// CHECK: must be the exact tree registered for this channel via configure_channel_compression
+    #[account(constraint = merkle_tree.key() == channel_account.compression_tree @ PodComError::InvalidCompressionAccount)]
     pub merkle_tree: AccountInfo<'info>,
-    /// CHECK: Nullifier queue account
+    /// CHECK: must be the exact queue paired with merkle_tree for this channel
+    #[account(constraint = nullifier_queue.key() == channel_account.compression_queue @ PodComError::InvalidCompressionAccount)]
     pub nullifier_queue: AccountInfo<'info>,
-    /// CHECK: CPI authority PDA
+    /// CHECK: this program's own CPI authority PDA; the seeds constraint
+    /// below is what prevents a caller from substituting an authority it
+    /// controls to forge compressed-tree appends.
+    #[account(seeds = [CPI_AUTHORITY_SEED], bump)]
     pub cpi_authority_pda: AccountInfo<'info>,
 }
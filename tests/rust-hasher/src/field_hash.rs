@@ -0,0 +1,90 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasherDefault, Hasher};
+
+/// `Hasher` for keys that are already a uniformly distributed 32-byte BN254
+/// digest (as produced by `hash_to_bn254_field_size_be`) - reads the first 8
+/// bytes straight into its internal state instead of rehashing them with
+/// SipHash, which would be wasted work on an already-uniform input.
+///
+/// Works whether the key's bytes arrive in one `write()` call or across
+/// several. The slice length prefix that `[u8; 32]`'s `Hash` impl writes
+/// before the key bytes (via `write_usize`) is ignored, so only the first 8
+/// key bytes end up in `state`.
+#[derive(Default)]
+pub struct FieldHasher {
+    state: u64,
+    filled: u8,
+    total_len: usize,
+}
+
+impl Hasher for FieldHasher {
+    fn finish(&self) -> u64 {
+        debug_assert!(
+            self.total_len >= 32,
+            "FieldHasher expects a 32-byte pre-hashed key, only saw {} bytes",
+            self.total_len
+        );
+        self.state
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.total_len += bytes.len();
+        for &b in bytes {
+            if self.filled < 8 {
+                self.state |= (b as u64) << (8 * self.filled);
+                self.filled += 1;
+            }
+        }
+    }
+
+    fn write_usize(&mut self, _len_prefix: usize) {}
+}
+
+pub type FieldBuildHasher = BuildHasherDefault<FieldHasher>;
+pub type FieldHashMap<V> = HashMap<[u8; 32], V, FieldBuildHasher>;
+pub type FieldHashSet = HashSet<[u8; 32], FieldBuildHasher>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(byte: u8) -> [u8; 32] {
+        let mut k = [0u8; 32];
+        k[0] = byte;
+        k
+    }
+
+    #[test]
+    fn set_dedups_identical_keys() {
+        let mut set = FieldHashSet::default();
+        assert!(set.insert(key(1)));
+        assert!(!set.insert(key(1)));
+        assert!(set.insert(key(2)));
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn keys_sharing_a_hash_bucket_are_still_kept_distinct() {
+        // These two keys share their first 8 bytes, so FieldHasher produces
+        // the same bucket for both - the set must still fall back to Eq and
+        // store them as two separate entries rather than losing one.
+        let mut a = key(7);
+        let mut b = key(7);
+        a[31] = 0xAA;
+        b[31] = 0xBB;
+
+        let mut set = FieldHashSet::default();
+        assert!(set.insert(a));
+        assert!(set.insert(b));
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn map_keeps_distinct_values_per_key() {
+        let mut map: FieldHashMap<&str> = FieldHashMap::default();
+        map.insert(key(1), "one");
+        map.insert(key(2), "two");
+        assert_eq!(map.get(&key(1)), Some(&"one"));
+        assert_eq!(map.get(&key(2)), Some(&"two"));
+    }
+}
@@ -1,7 +1,208 @@
 use light_hasher::hash_to_field_size::hash_to_bn254_field_size_be;
 
+mod field_hash;
+mod hashing;
+mod merkle;
+mod store;
+
+use field_hash::FieldHashSet;
+use hashing::{Algo, StreamingHasher};
+use store::{FieldStore, FsFieldStore};
+
 fn main() {
-    let input = std::env::args().nth(1).unwrap_or_default();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        Some("merkle") => run_merkle(&args[1..]),
+        Some("put") => run_put(&args[1..]),
+        Some("get") => run_get(&args[1..]),
+        Some("dedup") => run_dedup(&args[1..]),
+        Some("digest") => run_digest(&args[1..]),
+        _ => run_hash(&args),
+    }
+}
+
+/// Original behavior: hash a single CLI argument (or empty string) to a
+/// BN254 field element and print it as hex.
+fn run_hash(args: &[String]) {
+    let input = args.first().cloned().unwrap_or_default();
     let hash = hash_to_bn254_field_size_be(input.as_bytes());
     println!("{}", hex::encode(hash));
 }
+
+/// `merkle [--depth N] [--index I] [input ...]` - hash each input to a BN254
+/// leaf, build a fixed-depth Poseidon tree, and print the root plus an
+/// inclusion proof for leaf `I`. Inputs come from trailing positional args,
+/// or newline-delimited stdin if none are given.
+fn run_merkle(args: &[String]) {
+    let mut depth = merkle::DEFAULT_TREE_DEPTH;
+    let mut index = 0usize;
+    let mut inputs: Vec<String> = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--depth" => {
+                depth = args[i + 1].parse().expect("--depth expects an integer");
+                i += 2;
+            }
+            "--index" => {
+                index = args[i + 1].parse().expect("--index expects an integer");
+                i += 2;
+            }
+            other => {
+                inputs.push(other.to_string());
+                i += 1;
+            }
+        }
+    }
+
+    if inputs.is_empty() {
+        inputs = std::io::stdin()
+            .lines()
+            .map(|line| line.expect("valid utf8 stdin"))
+            .filter(|line| !line.is_empty())
+            .collect();
+    }
+
+    let leaves: Vec<[u8; 32]> = inputs
+        .iter()
+        .map(|input| hash_to_bn254_field_size_be(input.as_bytes()))
+        .collect();
+
+    assert!(
+        index < leaves.len(),
+        "--index {index} is out of range for {} leaves",
+        leaves.len()
+    );
+
+    let levels = merkle::build_levels(&leaves, depth);
+    let root = merkle::root(&levels);
+    let proof = merkle::prove(&levels, index);
+
+    println!("root={}", hex::encode(root));
+    println!("leaf_index={}", proof.leaf_index);
+    for sibling in &proof.siblings {
+        println!("sibling={}", hex::encode(sibling));
+    }
+}
+
+/// `put [--store-dir DIR]` - write all of stdin into the content-addressed
+/// store and print its field-hash key as hex.
+fn run_put(args: &[String]) {
+    let store_dir = store_dir_arg(args);
+    let mut store = FsFieldStore::open(store_dir).expect("opening blob store");
+
+    let mut value = Vec::new();
+    std::io::Read::read_to_end(&mut std::io::stdin(), &mut value).expect("reading stdin");
+
+    let key = store.insert(&value);
+    println!("{}", hex::encode(key));
+}
+
+/// `get <hex-key> [--store-dir DIR]` - fetch a blob by its field-hash key
+/// and write it to stdout.
+fn run_get(args: &[String]) {
+    let store_dir = store_dir_arg(args);
+    let store = FsFieldStore::open(store_dir).expect("opening blob store");
+
+    let key_hex = args
+        .iter()
+        .enumerate()
+        .find(|(i, a)| !a.starts_with("--") && args.get(i.wrapping_sub(1)).map(String::as_str) != Some("--store-dir"))
+        .map(|(_, a)| a)
+        .expect("get requires a hex key argument");
+    let key_bytes = hex::decode(key_hex).expect("key must be valid hex");
+    let key: store::FieldHash = key_bytes.try_into().expect("key must be 32 bytes");
+
+    match store.lookup(&key) {
+        Some(value) => {
+            std::io::Write::write_all(&mut std::io::stdout(), value).expect("writing stdout");
+        }
+        None => {
+            eprintln!("no blob for key {key_hex}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// `dedup [input ...]` - hash each input to a BN254 field element and report
+/// how many distinct values and how many repeats were seen. Inputs come from
+/// trailing positional args, or newline-delimited stdin if none are given.
+/// Uses `FieldHashSet` so de-duplicating a large input set skips the cost of
+/// rehashing already-uniform field elements with SipHash.
+fn run_dedup(args: &[String]) {
+    let mut inputs: Vec<String> = args.to_vec();
+    if inputs.is_empty() {
+        inputs = std::io::stdin()
+            .lines()
+            .map(|line| line.expect("valid utf8 stdin"))
+            .filter(|line| !line.is_empty())
+            .collect();
+    }
+
+    let mut seen = FieldHashSet::default();
+    let mut collisions = 0usize;
+    for input in &inputs {
+        let hash = hash_to_bn254_field_size_be(input.as_bytes());
+        if !seen.insert(hash) {
+            collisions += 1;
+        }
+    }
+
+    println!("total={}", inputs.len());
+    println!("unique={}", seen.len());
+    println!("collisions={collisions}");
+}
+
+/// `digest [--domain TAG] [--algo bn254|sha3] [part ...]` - stream each
+/// positional argument through a domain-separated, length-prefixed digest
+/// and print the result as hex. Parts come from trailing positional args, or
+/// newline-delimited stdin if none are given. `--algo` defaults to `bn254`;
+/// `--domain` defaults to the empty string.
+fn run_digest(args: &[String]) {
+    let mut domain = String::new();
+    let mut algo = Algo::Bn254;
+    let mut parts: Vec<String> = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--domain" => {
+                domain = args[i + 1].clone();
+                i += 2;
+            }
+            "--algo" => {
+                algo = Algo::parse(&args[i + 1])
+                    .unwrap_or_else(|| panic!("unknown --algo {:?}, expected bn254 or sha3", args[i + 1]));
+                i += 2;
+            }
+            other => {
+                parts.push(other.to_string());
+                i += 1;
+            }
+        }
+    }
+
+    if parts.is_empty() {
+        parts = std::io::stdin()
+            .lines()
+            .map(|line| line.expect("valid utf8 stdin"))
+            .filter(|line| !line.is_empty())
+            .collect();
+    }
+
+    let mut hasher = StreamingHasher::new(algo, &domain);
+    for part in &parts {
+        hasher.update(part.as_bytes());
+    }
+    println!("{}", hex::encode(hasher.finalize()));
+}
+
+fn store_dir_arg(args: &[String]) -> String {
+    args.iter()
+        .position(|a| a == "--store-dir")
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+        .unwrap_or_else(|| store::DEFAULT_STORE_DIR.to_string())
+}
@@ -0,0 +1,133 @@
+use light_hasher::hash_to_field_size::hash_to_bn254_field_size_be;
+use light_hasher::{Hasher, Poseidon};
+
+/// Depth used when the caller doesn't request a specific one. Fixed so that
+/// proofs produced by independent runs of this tool are uniform length.
+pub const DEFAULT_TREE_DEPTH: usize = 20;
+
+/// Bottom-up sibling path for one leaf, plus the index that tells a verifier
+/// which side of each sibling the running hash sits on.
+pub struct MerkleProof {
+    pub leaf_index: usize,
+    pub siblings: Vec<[u8; 32]>,
+}
+
+/// Canonical empty-leaf value used to pad the tree up to a power of two -
+/// the BN254 field reduction of the empty string, same function real leaves
+/// go through.
+fn empty_leaf() -> [u8; 32] {
+    hash_to_bn254_field_size_be(&[])
+}
+
+/// Combine two BN254 field elements into their Poseidon parent. Both inputs
+/// are already field-reduced (leaves via `hash_to_bn254_field_size_be`,
+/// internal nodes via this same function), so no extra reduction is needed.
+fn poseidon2(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(left);
+    buf[32..].copy_from_slice(right);
+    Poseidon::hash(&buf).expect("poseidon hash of two BN254 field elements")
+}
+
+/// Build every level of a fixed-depth binary tree over `leaves`, padded after
+/// the last real leaf with `empty_leaf()` up to `1 << depth` slots, so leaf
+/// `i` of `leaves` sits at index `i`. `levels[0]` is the leaf
+/// row, `levels[depth]` is the single-element root row.
+pub fn build_levels(leaves: &[[u8; 32]], depth: usize) -> Vec<Vec<[u8; 32]>> {
+    let capacity = 1usize << depth;
+    assert!(
+        leaves.len() <= capacity,
+        "{} leaves do not fit in a depth-{depth} tree (capacity {capacity})",
+        leaves.len()
+    );
+
+    let mut level = leaves.to_vec();
+    level.resize(capacity, empty_leaf());
+
+    let mut levels = vec![level];
+    for _ in 0..depth {
+        let prev = levels.last().expect("at least one level exists");
+        let next: Vec<[u8; 32]> = prev
+            .chunks_exact(2)
+            .map(|pair| poseidon2(&pair[0], &pair[1]))
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+pub fn root(levels: &[Vec<[u8; 32]>]) -> [u8; 32] {
+    levels.last().expect("at least one level exists")[0]
+}
+
+/// Sibling path for `leaf_index`, bottom-up.
+pub fn prove(levels: &[Vec<[u8; 32]>], leaf_index: usize) -> MerkleProof {
+    let mut siblings = Vec::with_capacity(levels.len() - 1);
+    let mut idx = leaf_index;
+    for level in &levels[..levels.len() - 1] {
+        siblings.push(level[idx ^ 1]);
+        idx /= 2;
+    }
+    MerkleProof { leaf_index, siblings }
+}
+
+/// Recompute the root from a leaf and its proof, folding upward: at level
+/// `k`, bit `k` of the leaf index selects whether `current` is the left or
+/// right argument to `poseidon2`.
+pub fn verify(proof: &MerkleProof, leaf: [u8; 32], expected_root: [u8; 32]) -> bool {
+    let mut current = leaf;
+    for (level, sibling) in proof.siblings.iter().enumerate() {
+        current = if (proof.leaf_index >> level) & 1 == 0 {
+            poseidon2(&current, sibling)
+        } else {
+            poseidon2(sibling, &current)
+        };
+    }
+    current == expected_root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(n: usize) -> Vec<[u8; 32]> {
+        (0..n)
+            .map(|i| hash_to_bn254_field_size_be(i.to_string().as_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn proof_round_trips_for_every_leaf() {
+        let depth = 4;
+        let leaves = leaves(11);
+        let levels = build_levels(&leaves, depth);
+        let root = root(&levels);
+
+        for (i, leaf) in leaves.iter().enumerate() {
+            let proof = prove(&levels, i);
+            assert_eq!(proof.leaf_index, i);
+            assert!(verify(&proof, *leaf, root));
+        }
+    }
+
+    #[test]
+    fn proof_fails_against_wrong_leaf_or_root() {
+        let depth = 4;
+        let leaves = leaves(5);
+        let levels = build_levels(&leaves, depth);
+        let root = root(&levels);
+        let proof = prove(&levels, 2);
+
+        assert!(!verify(&proof, leaves[3], root));
+        assert!(!verify(&proof, leaves[2], empty_leaf()));
+    }
+
+    #[test]
+    fn padding_leaves_hash_to_empty_leaf() {
+        let depth = 3;
+        let leaves = leaves(1);
+        let levels = build_levels(&leaves, depth);
+        assert_eq!(levels[0][1], empty_leaf());
+        assert_eq!(levels[0].len(), 1 << depth);
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use light_hasher::hash_to_field_size::hash_to_bn254_field_size_be;
+
+pub type FieldHash = [u8; 32];
+
+/// Content-addressed blob store keyed on the BN254 field digest of the
+/// value, so the key is exactly the circuit-compatible hash callers already
+/// need - no second hashing pass to get a storage key.
+pub trait FieldStore {
+    fn insert(&mut self, value: &[u8]) -> FieldHash;
+    fn lookup(&self, key: &FieldHash) -> Option<&[u8]>;
+    fn exists(&self, key: &FieldHash) -> bool;
+    fn kill(&mut self, key: &FieldHash);
+}
+
+/// Default directory the CLI stores blobs under when `--store-dir` isn't given.
+pub const DEFAULT_STORE_DIR: &str = ".rust-hasher-store";
+
+/// Filesystem-backed `FieldStore`. Blobs live under `root/<first two hex
+/// chars>/<remaining hex chars>` so large stores don't dump everything into
+/// one directory. The whole store is read into memory on open so
+/// `lookup`/`exists` can stay plain `&self` borrows.
+pub struct FsFieldStore {
+    root: PathBuf,
+    cache: HashMap<FieldHash, Vec<u8>>,
+}
+
+fn shard_path(root: &Path, key: &FieldHash) -> PathBuf {
+    let hex = hex::encode(key);
+    root.join(&hex[..2]).join(&hex[2..])
+}
+
+fn parse_key(shard: &str, rest: &str) -> Option<FieldHash> {
+    let hex = format!("{shard}{rest}");
+    let bytes = hex::decode(hex).ok()?;
+    bytes.try_into().ok()
+}
+
+impl FsFieldStore {
+    pub fn open(root: impl Into<PathBuf>) -> io::Result<Self> {
+        let root = root.into();
+        fs::create_dir_all(&root)?;
+
+        let mut cache = HashMap::new();
+        for shard_entry in fs::read_dir(&root)? {
+            let shard_entry = shard_entry?;
+            if !shard_entry.file_type()?.is_dir() {
+                continue;
+            }
+            let shard_name = shard_entry.file_name();
+            let Some(shard_name) = shard_name.to_str() else { continue };
+
+            for blob_entry in fs::read_dir(shard_entry.path())? {
+                let blob_entry = blob_entry?;
+                let blob_name = blob_entry.file_name();
+                let Some(blob_name) = blob_name.to_str() else { continue };
+                if let Some(key) = parse_key(shard_name, blob_name) {
+                    cache.insert(key, fs::read(blob_entry.path())?);
+                }
+            }
+        }
+
+        Ok(Self { root, cache })
+    }
+}
+
+impl FieldStore for FsFieldStore {
+    fn insert(&mut self, value: &[u8]) -> FieldHash {
+        let key = hash_to_bn254_field_size_be(value);
+        let path = shard_path(&self.root, &key);
+        // Content-addressed: same key always means same bytes, so a repeat
+        // insert is a harmless no-op write rather than a conflict to detect.
+        if !self.cache.contains_key(&key) {
+            fs::create_dir_all(path.parent().expect("shard path always has a parent"))
+                .and_then(|_| fs::write(&path, value))
+                .expect("writing a blob to the content-addressed store");
+            self.cache.insert(key, value.to_vec());
+        }
+        key
+    }
+
+    fn lookup(&self, key: &FieldHash) -> Option<&[u8]> {
+        self.cache.get(key).map(Vec::as_slice)
+    }
+
+    fn exists(&self, key: &FieldHash) -> bool {
+        self.cache.contains_key(key)
+    }
+
+    fn kill(&mut self, key: &FieldHash) {
+        if self.cache.remove(key).is_some() {
+            let _ = fs::remove_file(shard_path(&self.root, key));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store_dir(tag: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "rust-hasher-store-test-{tag}-{}",
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn put_then_get_round_trips() {
+        let dir = temp_store_dir("roundtrip");
+        let mut store = FsFieldStore::open(&dir).expect("opening blob store");
+
+        let key = store.insert(b"hello world");
+        assert!(store.exists(&key));
+        assert_eq!(store.lookup(&key), Some(&b"hello world"[..]));
+
+        // Re-opening from disk should rebuild the same cache.
+        let reopened = FsFieldStore::open(&dir).expect("reopening blob store");
+        assert_eq!(reopened.lookup(&key), Some(&b"hello world"[..]));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn repeat_insert_of_same_value_is_a_no_op() {
+        let dir = temp_store_dir("dedup-insert");
+        let mut store = FsFieldStore::open(&dir).expect("opening blob store");
+
+        let first = store.insert(b"same bytes");
+        let second = store.insert(b"same bytes");
+        assert_eq!(first, second);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn kill_removes_from_cache_and_disk() {
+        let dir = temp_store_dir("kill");
+        let mut store = FsFieldStore::open(&dir).expect("opening blob store");
+
+        let key = store.insert(b"ephemeral");
+        assert!(store.exists(&key));
+
+        store.kill(&key);
+        assert!(!store.exists(&key));
+        assert_eq!(store.lookup(&key), None);
+        assert!(!shard_path(&dir, &key).exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}